@@ -0,0 +1,231 @@
+use std::{
+    env,
+    fs,
+    path::PathBuf,
+    process::{self, Command},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use adventofcode_2022::profile::{self, Phase};
+
+const DAYS: u32 = 25;
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match args.first().map(|arg| arg.as_str()) {
+        Some("profile") => profile_days(&parse_days(&args[1..])),
+        Some("watch") if args.len() == 2 => watch(&parse_days(&args[1..])[0]),
+        _ => usage(),
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("Usage:");
+    eprintln!("  aoc profile [day...]    Profile every phase of the given days (all days by default)");
+    eprintln!("  aoc watch <day>         Rebuild and re-run a day whenever its source, sample or input changes");
+    process::exit(2);
+}
+
+fn parse_days(args: &[String]) -> Vec<String> {
+    if args.is_empty() {
+        return (1..=DAYS).map(|day| format!("day{:02}", day)).collect();
+    }
+
+    args
+        .iter()
+        .map(|arg| {
+            let number = arg.strip_prefix("day").unwrap_or(arg);
+
+            match number.parse::<u32>() {
+                Ok(day) if (1..=DAYS).contains(&day) => format!("day{:02}", day),
+                _ => {
+                    eprintln!("Invalid day '{}'.", arg);
+                    usage();
+                }
+            }
+        })
+        .collect()
+}
+
+fn manifest_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+fn binary(day: &str) -> PathBuf {
+    let target = env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| manifest_dir().join("target"));
+
+    target.join("release").join(day)
+}
+
+fn cargo() -> Command {
+    let mut command = Command::new(env::var("CARGO").unwrap_or_else(|_| "cargo".to_string()));
+    command.current_dir(manifest_dir());
+    command
+}
+
+fn build(days: &[String], features: &[&str]) -> bool {
+    let mut command = cargo();
+    command.args(["build", "--release"]);
+
+    if !features.is_empty() {
+        command.args(["--features", &features.join(",")]);
+    }
+
+    for day in days {
+        command.args(["--bin", day]);
+    }
+
+    command.status().map(|status| status.success()).unwrap_or(false)
+}
+
+fn profile_days(days: &[String]) {
+    if !build(days, &["profile"]) {
+        eprintln!("Build failed.");
+        process::exit(1);
+    }
+
+    let mut rows = vec![];
+
+    for day in days {
+        let output = match Command::new(binary(day)).arg("--profile=tsv").output() {
+            Ok(output) => output,
+            Err(error) => {
+                eprintln!("Could not run {}: {}", day, error);
+                continue;
+            }
+        };
+
+        if !output.status.success() {
+            eprintln!("{} exited with {}.", day, output.status);
+        }
+
+        // Anything else the solvers print is ignored
+        rows.extend(
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter_map(profile::from_tsv)
+        );
+    }
+
+    // Nested phases are already part of their parent's figures
+    let top_level: Vec<&Phase> = rows
+        .iter()
+        .map(|(_, phase)| phase)
+        .filter(|phase| !phase.name.contains('/'))
+        .collect();
+
+    let total = Phase {
+        name: String::new(),
+        elapsed: top_level.iter().map(|phase| phase.elapsed).sum::<Duration>(),
+        peak: top_level.iter().map(|phase| phase.peak).max().unwrap_or(0),
+        allocations: top_level.iter().map(|phase| phase.allocations).sum(),
+        allocated: top_level.iter().map(|phase| phase.allocated).sum(),
+    };
+
+    rows.push(("total".to_string(), total));
+    profile::print_table(&rows, true);
+}
+
+fn watch(day: &str) {
+    let files = [
+        manifest_dir().join("src").join(format!("{}.rs", day)),
+        manifest_dir().join("sample").join(format!("{}.input", day)),
+        manifest_dir().join("input").join(format!("{}.input", day)),
+    ];
+
+    let mut previous = None;
+    let mut seen = modified(&files);
+
+    loop {
+        println!("=== {} ===", day);
+        previous = run_watched(day, previous);
+
+        println!("Watching for changes...");
+        loop {
+            thread::sleep(POLL_INTERVAL);
+
+            let current = modified(&files);
+            if current != seen {
+                seen = current;
+                break;
+            }
+        }
+    }
+}
+
+// Missing files simply count as never modified, the build will complain about them
+fn modified(files: &[PathBuf]) -> Vec<Option<SystemTime>> {
+    files
+        .iter()
+        .map(|file| fs::metadata(file).and_then(|metadata| metadata.modified()).ok())
+        .collect()
+}
+
+// Returns the answers to diff the next run against, keeping the old ones if this run failed
+fn run_watched(day: &str, previous: Option<Vec<String>>) -> Option<Vec<String>> {
+    if !build(&[day.to_string()], &[]) {
+        println!("Build failed.");
+        return previous;
+    }
+
+    let tests = cargo()
+        .args(["test", "--release", "--bin", day])
+        .output();
+
+    match tests {
+        Ok(output) if output.status.success() => println!("Sample tests passed."),
+        Ok(output) => {
+            println!("Sample tests failed:");
+            print!("{}", String::from_utf8_lossy(&output.stdout));
+        }
+        Err(error) => println!("Could not run the sample tests: {}", error),
+    }
+
+    let output = match Command::new(binary(day)).output() {
+        Ok(output) => output,
+        Err(error) => {
+            println!("Could not run {}: {}", day, error);
+            return previous;
+        }
+    };
+
+    if !output.status.success() {
+        print!("{}", String::from_utf8_lossy(&output.stderr));
+        println!("{} exited with {}.", day, output.status);
+        return previous;
+    }
+
+    let answers: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| line.to_string())
+        .collect();
+
+    match &previous {
+        Some(previous) => print_diff(previous, &answers),
+        None => answers.iter().for_each(|line| println!("  {}", line)),
+    }
+
+    Some(answers)
+}
+
+fn print_diff(previous: &[String], current: &[String]) {
+    for index in 0..previous.len().max(current.len()) {
+        match (previous.get(index), current.get(index)) {
+            (Some(old), Some(new)) if old == new => println!("  {}", new),
+            (old, new) => {
+                if let Some(old) = old {
+                    println!("- {}", old);
+                }
+
+                if let Some(new) = new {
+                    println!("+ {}", new);
+                }
+            }
+        }
+    }
+}
@@ -1,63 +1,250 @@
+use std::{cmp::Reverse, collections::BinaryHeap, io::{self, BufRead}};
+use adventofcode_2022::runner::{Day, Solution};
+
+// Keeps the k elves carrying the most calories seen so far, in O(k) memory
+struct TopK {
+    k: usize,
+    // Min-heap of the best k seen so far, its top is the one to evict. Ties go to the elf that comes first.
+    heap: BinaryHeap<Reverse<(u64, Reverse<usize>)>>
+}
+
+impl TopK {
+    fn new(k: usize) -> Self {
+        Self { k, heap: BinaryHeap::with_capacity(k + 1) }
+    }
+
+    fn push(&mut self, index: usize, total: u64) {
+        if self.k == 0 {
+            return;
+        }
+
+        self.heap.push(Reverse((total, Reverse(index))));
+
+        if self.heap.len() > self.k {
+            self.heap.pop();
+        }
+    }
+
+    // Returns (elf_index, total) pairs, highest first
+    fn into_ranked(self) -> Vec<(usize, u64)> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse((total, Reverse(index)))| (index, total))
+            .collect()
+    }
+}
+
+// The calories every elf carries, in order. Any run of blank lines separates two elves.
+struct Inventories<R> {
+    reader: R,
+    line: String
+}
+
+impl<R: BufRead> Inventories<R> {
+    fn new(reader: R) -> Self {
+        Self { reader, line: String::new() }
+    }
+}
+
+impl<R: BufRead> Iterator for Inventories<R> {
+    type Item = io::Result<u64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut current: Option<u64> = None;
+
+        loop {
+            self.line.clear();
+            let read = match self.reader.read_line(&mut self.line) {
+                Ok(read) => read,
+                Err(error) => return Some(Err(error))
+            };
+            let item = self.line.trim_end();
+
+            // The end of the input, or a blank line after some items, closes the current elf's inventory
+            if read == 0 {
+                return current.map(Ok);
+            }
+
+            if item.is_empty() {
+                if current.is_some() {
+                    return current.map(Ok);
+                }
+
+                continue;
+            }
+
+            match item.parse::<u64>() {
+                Ok(calories) => *current.get_or_insert(0) += calories,
+                Err(_) => return Some(Err(io::Error::new(io::ErrorKind::InvalidData, format!("Invalid number: {}", item))))
+            }
+        }
+    }
+}
+
+struct Statistics {
+    min: u64,
+    max: u64,
+    mean: f64,
+    median: f64
+}
+
 fn main() {
     let input = include_str!("../input/day01.input");
 
-    // Part one
-    let max = first_puzzle(input);
-    println!("The elf carrying the most calories has {} calories.", max);
-
-    // Part two
-    let top_three_sum = second_puzzle(input);
-    println!("The top three elves are carrying {} calories in total.", top_three_sum);
+    Day::new("day01", &[
+        "The elf carrying the most calories has {} calories.",
+        "The top three elves are carrying {} calories in total.",
+    ])
+    .solution(Solution::new("venustas").part(first_puzzle).part(second_puzzle))
+    .report("leaderboard", print_leaderboard)
+    .report("stream", print_streamed)
+    .run(input);
 }
 
 #[test]
 fn sample() {
     let sample = include_str!("../sample/day01.input");
-    
+
     assert_eq!(first_puzzle(sample), 24000);
     assert_eq!(second_puzzle(sample), 45000);
 }
 
+#[test]
+fn ranking() {
+    let sample = include_str!("../sample/day01.input");
+    let totals = elf_totals(sample);
+
+    assert_eq!(top_k(&totals, 2), vec![(3, 24000), (2, 11000)]);
+    assert_eq!(top_k(&totals, 10).len(), 5);
+    assert!(top_k(&totals, 0).is_empty());
+
+    // Streaming gives the same ranking, with or without a trailing newline
+    let (streamed, elves) = stream_top_k(sample.as_bytes(), 2).unwrap();
+    assert_eq!((streamed, elves), (vec![(3, 24000), (2, 11000)], 5));
+    assert_eq!(stream_top_k(sample.trim_end().as_bytes(), 5).unwrap().0, top_k(&totals, 5));
+    assert!(stream_top_k("1\nx\n".as_bytes(), 1).is_err());
+
+    // Runs of blank lines separate elves just once, however the inventory is read
+    let spaced = "1000\n\n\n\n2000\n3000\n\n\n500\n";
+    assert_eq!(elf_totals(spaced), vec![1000, 5000, 500]);
+    assert_eq!(stream_top_k(spaced.as_bytes(), 3).unwrap(), (top_k(&elf_totals(spaced), 3), 3));
+    assert_eq!(stream_top_k("\n\n7\n".as_bytes(), 1).unwrap(), (vec![(0, 7)], 1));
+
+    assert_eq!(parse_k(&[], 3), Ok(3));
+    assert_eq!(parse_k(&["2".to_string()], 3), Ok(2));
+    assert!(parse_k(&["-1".to_string()], 3).is_err());
+
+    let stats = statistics(&totals).unwrap();
+    assert_eq!((stats.min, stats.max, stats.mean, stats.median), (4000, 24000, 11000.0, 10000.0));
+}
+
 fn first_puzzle(source: &str) -> u64 {
-    source
-        .split("\n\n")
-        .map(|line|
-            line
-                .lines()
-                .map(to_u64)
-                .sum()
-        )
-        .max()
-        .unwrap()
+    let (top, _) = stream_top_k(source.as_bytes(), 1).expect("Could not read the inventory.");
+    top[0].1
 }
 
 fn second_puzzle(source: &str) -> u64 {
-    let mut top = [0, 0, 0];
-
-    source
-        .split("\n\n")
-        .map(|line|
-            line
-                .lines()
-                .map(to_u64)
-                .sum()
-        )
-        .for_each(|count| {
-            if count > top[0] {
-                top[2] = top[1];
-                top[1] = top[0];
-                top[0] = count;
-            } else if count > top[1] {
-                top[2] = top[1];
-                top[1] = count
-            } else if count > top[2] {
-                top[2] = count
-            }
-        });
+    let (top, _) = stream_top_k(source.as_bytes(), 3).expect("Could not read the inventory.");
+
+    top
+        .iter()
+        .map(|(_, total)| total)
+        .sum()
+}
+
+// Reads the inventory line by line, only ever holding the current line and the k best elves.
+// Also returns how many elves there were.
+fn stream_top_k<R: BufRead>(reader: R, k: usize) -> io::Result<(Vec<(usize, u64)>, usize)> {
+    let mut top = TopK::new(k);
+    let mut elves = 0;
+
+    for total in Inventories::new(reader) {
+        top.push(elves, total?);
+        elves += 1;
+    }
+
+    Ok((top.into_ranked(), elves))
+}
+
+fn elf_totals(source: &str) -> Vec<u64> {
+    Inventories::new(source.as_bytes())
+        .collect::<io::Result<Vec<u64>>>()
+        .expect("Number was in an invalid format.")
+}
+
+// Returns the k elves carrying the most calories as (elf_index, total), highest first
+fn top_k(totals: &[u64], k: usize) -> Vec<(usize, u64)> {
+    let mut top = TopK::new(k);
+
+    for (index, &total) in totals.iter().enumerate() {
+        top.push(index, total);
+    }
+
+    top.into_ranked()
+}
+
+fn statistics(totals: &[u64]) -> Option<Statistics> {
+    if totals.is_empty() {
+        return None;
+    }
+
+    let mut sorted = totals.to_vec();
+    sorted.sort_unstable();
+
+    let middle = sorted.len() / 2;
+    let median = if sorted.len().is_multiple_of(2) {
+        (sorted[middle - 1] + sorted[middle]) as f64 / 2.0
+    } else {
+        sorted[middle] as f64
+    };
+
+    Some(Statistics {
+        min: sorted[0],
+        max: sorted[sorted.len() - 1],
+        mean: sorted.iter().sum::<u64>() as f64 / sorted.len() as f64,
+        median
+    })
+}
+
+fn print_leaderboard(source: &str, args: &[String]) -> Result<(), String> {
+    let totals = elf_totals(source);
+    let k = parse_k(args, totals.len())?;
+
+    println!("{:>5}  {:>5}  {:>10}", "Rank", "Elf", "Calories");
+    for (rank, (index, total)) in top_k(&totals, k).iter().enumerate() {
+        println!("{:>5}  {:>5}  {:>10}", rank + 1, index + 1, total);
+    }
 
-    top.iter().sum()
+    if let Some(stats) = statistics(&totals) {
+        println!();
+        println!("{} elves, min {}, max {}, mean {:.1}, median {:.1}", totals.len(), stats.min, stats.max, stats.mean, stats.median);
+    }
+
+    Ok(())
+}
+
+// Ranks an inventory piped through stdin, which can be far larger than what fits in memory
+fn print_streamed(_: &str, args: &[String]) -> Result<(), String> {
+    let k = parse_k(args, 3)?;
+
+    let (top, elves) = stream_top_k(io::stdin().lock(), k).map_err(|error| error.to_string())?;
+
+    println!("{:>5}  {:>10}  {:>14}", "Rank", "Elf", "Calories");
+    for (rank, (index, total)) in top.iter().enumerate() {
+        println!("{:>5}  {:>10}  {:>14}", rank + 1, index + 1, total);
+    }
+
+    println!();
+    println!("{} elves, {} calories for the top {}", elves, top.iter().map(|(_, total)| total).sum::<u64>(), top.len());
+
+    Ok(())
 }
 
-fn to_u64(source: &str) -> u64 {
-    source.parse::<u64>().expect("Number was in an invalid format.")
-}
\ No newline at end of file
+// How many elves a report ranks, its first argument if there is one
+fn parse_k(args: &[String], default: usize) -> Result<usize, String> {
+    match args.first() {
+        Some(k) => k.parse::<usize>().map_err(|_| format!("Invalid number of elves '{}'.", k)),
+        None => Ok(default)
+    }
+}
@@ -1,27 +1,132 @@
-#[derive(PartialEq, Eq, Clone, Copy)]
-enum Play {
-    Rock,
-    Paper,
-    Scissors
-}
+use std::collections::HashMap;
+use adventofcode_2022::runner::{Day, Solution};
+
+// Moves are listed in dominance order: each one beats the half of the cycle right before it
+const CLASSIC: &str = "
+moves: Rock=1 Paper=2 Scissors=3
+opponent: A B C
+player: X Y Z
+scores: lose=0 draw=3 win=6
+outcomes: X=lose Y=draw Z=win
+";
 
-#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
 enum Outcome {
     Win,
     Lose,
     Draw
 }
 
+const OUTCOMES: [Outcome; 3] = [Outcome::Lose, Outcome::Draw, Outcome::Win];
+
+// What the guide's second column is taken to mean
+#[derive(Clone, Copy)]
+enum Meaning {
+    Moves,
+    Outcomes
+}
+
+// A move, as an index into the rules' cycle
+type Play = usize;
+
+struct Rules {
+    names: Vec<String>,
+    scores: Vec<i32>,
+    opponent: HashMap<String, Play>,
+    player: HashMap<String, Play>,
+    outcome_scores: HashMap<Outcome, i32>,
+    outcomes: HashMap<String, Outcome>
+}
+
+impl Rules {
+    fn parse(source: &str) -> Self {
+        let mut names = vec![];
+        let mut scores = vec![];
+        let mut opponent_letters = vec![];
+        let mut player_letters = vec![];
+        let mut outcome_scores = HashMap::new();
+        let mut outcomes = HashMap::new();
+
+        for line in source.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')) {
+            let (key, values) = line.split_once(':').expect("Expected 'key: values'.");
+            let values = values.split_whitespace();
+
+            match key.trim() {
+                "moves" => {
+                    for value in values {
+                        let (name, score) = value.split_once('=').expect("Expected 'Move=score'.");
+
+                        names.push(name.to_owned());
+                        scores.push(score.parse::<i32>().expect("Invalid score."));
+                    }
+                }
+                "opponent" => opponent_letters = values.map(str::to_owned).collect(),
+                "player" => player_letters = values.map(str::to_owned).collect(),
+                "scores" => {
+                    for value in values {
+                        let (outcome, score) = value.split_once('=').expect("Expected 'outcome=score'.");
+                        outcome_scores.insert(parse_outcome_name(outcome), score.parse::<i32>().expect("Invalid score."));
+                    }
+                }
+                "outcomes" => {
+                    for value in values {
+                        let (letter, outcome) = value.split_once('=').expect("Expected 'letter=outcome'.");
+                        outcomes.insert(letter.to_owned(), parse_outcome_name(outcome));
+                    }
+                }
+                other => panic!("Unknown rule: {}", other)
+            }
+        }
+
+        assert!(names.len() % 2 == 1, "A dominance cycle needs an odd number of moves.");
+        assert_eq!(opponent_letters.len(), names.len(), "Every move needs an opponent letter.");
+        assert_eq!(player_letters.len(), names.len(), "Every move needs a player letter.");
+        assert_eq!(outcome_scores.len(), 3, "Every outcome needs a score.");
+
+        let letters = |letters: Vec<String>| -> HashMap<String, Play> {
+            let map: HashMap<String, Play> = letters.into_iter().enumerate().map(|(play, letter)| (letter, play)).collect();
+            assert_eq!(map.len(), names.len(), "Letters must be unique.");
+            map
+        };
+
+        Self {
+            opponent: letters(opponent_letters),
+            player: letters(player_letters),
+            names,
+            scores,
+            outcome_scores,
+            outcomes
+        }
+    }
+
+    // The player's letters, ordered by the move they stand for
+    fn player_letters(&self) -> Vec<&str> {
+        let mut letters: Vec<(&str, Play)> = self.player.iter().map(|(letter, &play)| (letter.as_str(), play)).collect();
+        letters.sort_by_key(|(_, play)| *play);
+
+        letters.into_iter().map(|(letter, _)| letter).collect()
+    }
+
+    fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    fn beats(&self, play: Play, other: Play) -> bool {
+        let distance = (play + self.len() - other) % self.len();
+        distance >= 1 && distance <= self.len() / 2
+    }
+}
+
 fn main() {
     let input = include_str!("../input/day02.input");
 
-    // Part one
-    let points_incorrect = first_puzzle(input);
-    println!("Your final score would be {} points with the incorrectly decrypted guide", points_incorrect);
-
-    // Part two
-    let points_correct = second_puzzle(input);
-    println!("Your final score would be {} points with the correctly decrypted guide.", points_correct);
+    Day::new("day02", &[
+        "Your final score would be {} points with the incorrectly decrypted guide",
+        "Your final score would be {} points with the correctly decrypted guide.",
+    ])
+    .solution(Solution::new("venustas").part(first_puzzle).part(second_puzzle))
+    .report("decode", print_decodings)
+    .run(input);
 }
 
 #[test]
@@ -32,100 +137,203 @@ fn sample() {
     assert_eq!(second_puzzle(sample), 12);
 }
 
+#[test]
+fn lizard_spock() {
+    let rules = Rules::parse("
+        moves: Rock=1 Spock=5 Paper=2 Lizard=4 Scissors=3
+        opponent: A B C D E
+        player: V W X Y Z
+        scores: lose=0 draw=3 win=6
+        outcomes: X=lose Y=draw Z=win
+    ");
+
+    let name = |play: Play| rules.names[play].as_str();
+    let find = |name: &str| rules.names.iter().position(|n| n == name).unwrap();
+
+    assert!(rules.beats(find("Spock"), find("Scissors")));
+    assert!(rules.beats(find("Lizard"), find("Spock")));
+    assert!(rules.beats(find("Rock"), find("Lizard")));
+    assert!(!rules.beats(find("Rock"), find("Paper")));
+    assert!(!rules.beats(find("Rock"), find("Rock")));
+
+    // Spock (B) against Lizard (Y), then Scissors (E) against Rock (V)
+    assert_eq!(score(&rules, "B Y\nE V\n", parse_plays), 4 + 6 + 1 + 6);
+
+    // Two moves beat Rock, the one scoring the most is picked
+    assert_eq!(name(ensure_outcome(&rules, find("Rock"), Outcome::Win)), "Spock");
+    assert_eq!(name(ensure_outcome(&rules, find("Rock"), Outcome::Lose)), "Lizard");
+}
+
+#[test]
+fn decoding() {
+    let sample = include_str!("../sample/day02.input");
+    let rules = Rules::parse(CLASSIC);
+
+    let as_moves = decodings(&rules, sample, Meaning::Moves);
+    assert_eq!(as_moves.len(), 6);
+    assert_eq!(as_moves.iter().find(|(mapping, _)| *mapping == vec![0, 1, 2]).unwrap().1, 15);
+
+    let as_outcomes = decodings(&rules, sample, Meaning::Outcomes);
+    assert_eq!(as_outcomes.len(), 6);
+    assert_eq!(as_outcomes.iter().find(|(mapping, _)| *mapping == vec![0, 1, 2]).unwrap().1, 12);
+
+    // Every round is won with the best mapping: Y beats A, X beats B and Z beats C
+    assert_eq!(as_moves.iter().map(|(_, score)| *score).max(), Some(2 + 6 + 3 + 6 + 1 + 6));
+    assert_eq!(mappings(2, 3).len(), 6);
+    assert_eq!(mappings(4, 3).len(), 81);
+}
+
 fn first_puzzle(source: &str) -> i32 {
-    source
-        .lines()
-        .map(parse_plays)
-        .map(|(player, enemy)| points(player, play(player, enemy)))
-        .sum()
+    score(&Rules::parse(CLASSIC), source, parse_plays)
 }
 
 fn second_puzzle(source: &str) -> i32 {
+    score(&Rules::parse(CLASSIC), source, |rules, line| {
+        let (enemy, desired_outcome) = parse_outcome(rules, line);
+        (ensure_outcome(rules, enemy, desired_outcome), enemy)
+    })
+}
+
+// Totals the points of every round, `decode` turns a line of the guide into (player, enemy)
+fn score(rules: &Rules, source: &str, decode: impl Fn(&Rules, &str) -> (Play, Play)) -> i32 {
     source
         .lines()
-        .map(parse_outcome)
-        .map(|(enemy, desired_outcome)| (ensure_outcome(enemy, desired_outcome), enemy))
-        .map(|(player, enemy)| points(player, play(player, enemy)))
+        .map(|line| decode(rules, line))
+        .map(|(player, enemy)| points(rules, player, play(rules, player, enemy)))
         .sum()
 }
 
-fn parse_plays(line: &str) -> (Play, Play) {
-    let mut letters = line.split(' ').take(2);
-
-    let enemy = match letters.next().unwrap() {
-        "A" => Play::Rock,
-        "B" => Play::Paper,
-        "C" => Play::Scissors,
-        _ => unreachable!()
+// Scores the guide under every way its player letters could be mapped to moves or to outcomes.
+// Mappings are listed as the target index for each letter, in the order the rules list the letters.
+fn decodings(rules: &Rules, source: &str, meaning: Meaning) -> Vec<(Vec<usize>, i32)> {
+    let letters = rules.player_letters();
+    let targets = match meaning {
+        Meaning::Moves => rules.len(),
+        Meaning::Outcomes => OUTCOMES.len()
     };
 
-    let player = match letters.next().unwrap() {
-        "X" => Play::Rock,
-        "Y" => Play::Paper,
-        "Z" => Play::Scissors,
-        _ => unreachable!()
-    };
+    mappings(letters.len(), targets)
+        .into_iter()
+        .map(|mapping| {
+            let score = score(rules, source, |rules, line| {
+                let (enemy_letter, letter) = line.split_once(' ').expect("Invalid format.");
+                let enemy = rules.opponent[enemy_letter];
+                let target = mapping[letters.iter().position(|l| *l == letter).expect("Unknown letter.")];
 
-    (player, enemy)
+                match meaning {
+                    Meaning::Moves => (target, enemy),
+                    Meaning::Outcomes => (ensure_outcome(rules, enemy, OUTCOMES[target]), enemy)
+                }
+            });
+
+            (mapping, score)
+        })
+        .collect()
 }
 
-fn parse_outcome(line: &str) -> (Play, Outcome) {
-    let mut letters = line.split(' ').take(2);
+// Every injective way to assign `letters` letters to `targets` targets. With more letters than
+// targets some must share one, so every assignment is listed instead.
+fn mappings(letters: usize, targets: usize) -> Vec<Vec<usize>> {
+    let mut result = vec![];
+    let mut current = vec![];
 
-    let enemy = match letters.next().unwrap() {
-        "A" => Play::Rock,
-        "B" => Play::Paper,
-        "C" => Play::Scissors,
-        _ => unreachable!()
-    };
+    extend_mapping(letters, targets, letters <= targets, &mut current, &mut result);
+    result
+}
 
-    let desired_outcome = match letters.next().unwrap() {
-        "X" => Outcome::Lose,
-        "Y" => Outcome::Draw,
-        "Z" => Outcome::Win,
-        _ => unreachable!()
-    };
+fn extend_mapping(letters: usize, targets: usize, injective: bool, current: &mut Vec<usize>, result: &mut Vec<Vec<usize>>) {
+    if current.len() == letters {
+        result.push(current.clone());
+        return;
+    }
 
-    (enemy, desired_outcome)
+    for target in 0..targets {
+        if injective && current.contains(&target) {
+            continue;
+        }
+
+        current.push(target);
+        extend_mapping(letters, targets, injective, current, result);
+        current.pop();
+    }
 }
 
-fn ensure_outcome(enemy: Play, desired_outcome: Outcome) -> Play {
-    match desired_outcome {
-        Outcome::Win => wins_against(enemy),
-        Outcome::Lose => wins_against(wins_against(enemy)),
-        Outcome::Draw => enemy,
+fn print_decodings(source: &str, _: &[String]) -> Result<(), String> {
+    let rules = Rules::parse(CLASSIC);
+    let letters = rules.player_letters();
+
+    for (title, meaning) in [("moves", Meaning::Moves), ("outcomes", Meaning::Outcomes)] {
+        let results = decodings(&rules, source, meaning);
+        let describe = |mapping: &[usize]| -> String {
+            letters
+                .iter()
+                .zip(mapping)
+                .map(|(letter, &target)| match meaning {
+                    Meaning::Moves => format!("{}={}", letter, rules.names[target]),
+                    Meaning::Outcomes => format!("{}={:?}", letter, OUTCOMES[target])
+                })
+                .collect::<Vec<String>>()
+                .join(" ")
+        };
+
+        println!("Letters as {}:", title);
+        for (mapping, score) in &results {
+            println!("  {:<32}  {:>8}", describe(mapping), score);
+        }
+
+        let (best_mapping, best) = results.iter().max_by_key(|(_, score)| *score).unwrap();
+        let (worst_mapping, worst) = results.iter().min_by_key(|(_, score)| *score).unwrap();
+        let expected = results.iter().map(|(_, score)| *score as f64).sum::<f64>() / results.len() as f64;
+
+        println!("  best {} ({}), worst {} ({}), expected {:.2}", best, describe(best_mapping), worst, describe(worst_mapping), expected);
+        println!();
     }
+
+    Ok(())
 }
 
-fn play(player: Play, enemy: Play) -> Outcome {
-    if player == wins_against(enemy) { return Outcome::Win }
-    if enemy == wins_against(player) { return Outcome::Lose }
+fn parse_plays(rules: &Rules, line: &str) -> (Play, Play) {
+    let mut letters = line.split(' ').take(2);
 
-    Outcome::Draw
+    let enemy = rules.opponent[letters.next().unwrap()];
+    let player = rules.player[letters.next().unwrap()];
+
+    (player, enemy)
 }
 
-fn wins_against(play: Play) -> Play {
-    match play {
-        Play::Rock => Play::Paper,
-        Play::Paper => Play::Scissors,
-        Play::Scissors => Play::Rock
+fn parse_outcome(rules: &Rules, line: &str) -> (Play, Outcome) {
+    let mut letters = line.split(' ').take(2);
+
+    let enemy = rules.opponent[letters.next().unwrap()];
+    let desired_outcome = rules.outcomes[letters.next().unwrap()];
+
+    (enemy, desired_outcome)
+}
+
+fn parse_outcome_name(name: &str) -> Outcome {
+    match name {
+        "win" => Outcome::Win,
+        "lose" => Outcome::Lose,
+        "draw" => Outcome::Draw,
+        _ => panic!("Unknown outcome: {}", name)
     }
 }
 
-fn points(play: Play, outcome: Outcome) -> i32 {
-    let mut count = 0;
+// With more than three moves several of them may do, so the one scoring the most is picked
+fn ensure_outcome(rules: &Rules, enemy: Play, desired_outcome: Outcome) -> Play {
+    (0..rules.len())
+        .filter(|&player| play(rules, player, enemy) == desired_outcome)
+        .max_by_key(|&player| (rules.scores[player], std::cmp::Reverse(player)))
+        .unwrap()
+}
 
-    count += match play {
-        Play::Rock => 1,
-        Play::Paper => 2,
-        Play::Scissors => 3
-    };
+fn play(rules: &Rules, player: Play, enemy: Play) -> Outcome {
+    if rules.beats(player, enemy) { return Outcome::Win }
+    if rules.beats(enemy, player) { return Outcome::Lose }
 
-    count += match outcome {
-        Outcome::Win => 6,
-        Outcome::Lose => 0,
-        Outcome::Draw => 3
-    };
+    Outcome::Draw
+}
 
-    count
-}
\ No newline at end of file
+fn points(rules: &Rules, play: Play, outcome: Outcome) -> i32 {
+    rules.scores[play] + rules.outcome_scores[&outcome]
+}
@@ -1,13 +1,52 @@
+use std::ops::BitAnd;
+use adventofcode_2022::runner::{Day, Solution};
+
+// A set of item types, one bit per priority: a-z are bits 0-25 and A-Z are bits 26-51
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct ItemSet(u64);
+
+impl ItemSet {
+    const ALL: ItemSet = ItemSet((1 << 52) - 1);
+
+    fn from_items(items: &str) -> Self {
+        items
+            .chars()
+            .fold(ItemSet::default(), |set, item| ItemSet(set.0 | 1 << (priority(item) - 1)))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    fn items(&self) -> impl Iterator<Item = char> + '_ {
+        (0..52)
+            .filter(|bit| self.0 & (1 << bit) != 0)
+            .map(|bit| item(bit + 1))
+    }
+
+    fn priority_sum(&self) -> u64 {
+        self.items().map(priority).sum()
+    }
+}
+
+impl BitAnd for ItemSet {
+    type Output = ItemSet;
+
+    fn bitand(self, other: ItemSet) -> ItemSet {
+        ItemSet(self.0 & other.0)
+    }
+}
+
 fn main() {
     let input = include_str!("../input/day03.input");
 
-    // Part one
-    let total = first_puzzle(input);
-    println!("The sum of all priorities is {}.", total);
-
-    // Part two
-    let total = second_puzzle(input);
-    println!("The sum of the priorities of all badges is {}.", total);
+    Day::new("day03", &[
+        "The sum of all priorities is {}.",
+        "The sum of the priorities of all badges is {}.",
+    ])
+    .solution(Solution::new("raquel").part(first_puzzle).part(second_puzzle))
+    .report("shared", print_shared)
+    .run(input);
 }
 
 #[test]
@@ -18,67 +57,93 @@ fn sample() {
     assert_eq!(second_puzzle(sample), 70);
 }
 
+#[test]
+fn item_sets() {
+    let set = ItemSet::from_items("aAzZa");
+    assert_eq!(set.items().collect::<String>(), "azAZ");
+    assert_eq!(set.priority_sum(), 1 + 26 + 27 + 52);
+
+    // Nothing in common is an empty set, not an error
+    assert!(common(&[ItemSet::from_items("abc"), ItemSet::from_items("xyz")]).is_empty());
+    assert_eq!(common(&[]), ItemSet::ALL);
+
+    assert!(shared_in_compartments("abcaXbYc", 4).is_empty());
+    assert_eq!(shared_in_compartments("abXaYbZa", 2), ItemSet::from_items("ab"));
+
+    // Compartments of 2, 2 and 3 items, and more compartments than items
+    assert_eq!(shared_in_compartments("abbaXab", 3), ItemSet::from_items("ab"));
+    assert_eq!(shared_in_compartments("aXaYa", 2), ItemSet::from_items("a"));
+    assert!(shared_in_compartments("aa", 3).is_empty());
+    assert_eq!(shared_in_groups("ab\nbc\nbd\nb", 2), vec![ItemSet::from_items("b"), ItemSet::from_items("b")]);
+}
+
 fn first_puzzle(source: &str) -> u64 {
     source
         .lines()
-        .map(|line| line.split_at(line.len() / 2))
-        .map(|(first, second)| priority(find_match(first, second)))
+        .map(|line| shared_in_compartments(line, 2).priority_sum())
         .sum()
 }
 
 fn second_puzzle(source: &str) -> u64 {
-    let mut lines = source.lines().peekable();
-    let mut count = 0;
+    shared_in_groups(source, 3)
+        .iter()
+        .map(|badges| badges.priority_sum())
+        .sum()
+}
 
-    loop {
-        if lines.peek().is_none() {
-            break;
-        }
+// Items found in every one of the rucksack's compartments. When the items don't split evenly
+// the compartments differ in size by at most one, the larger ones coming last.
+fn shared_in_compartments(rucksack: &str, compartments: usize) -> ItemSet {
+    assert!(rucksack.is_ascii(), "Invalid format");
 
-        let line1 = lines.next().unwrap();
-        let _ = lines.next().unwrap();
-        let line3 = lines.next().unwrap();
+    let bound = |index: usize| index * rucksack.len() / compartments;
+    let sets: Vec<ItemSet> = (0..compartments)
+        .map(|index| ItemSet::from_items(&rucksack[bound(index)..bound(index + 1)]))
+        .collect();
 
-        // Take the first line slice and the third line slice to form a slice that contains all three lines
-        let start = line1.as_ptr() as usize - source.as_ptr() as usize;
-        let end = line3.as_ptr() as usize - source.as_ptr() as usize + line3.len();
+    common(&sets)
+}
 
-        count += priority(find_badge(&source[start..end]));
-    }
+// Items carried by every elf of each group. A trailing group may be smaller than the rest.
+fn shared_in_groups(source: &str, group_size: usize) -> Vec<ItemSet> {
+    let rucksacks: Vec<ItemSet> = source
+        .lines()
+        .map(ItemSet::from_items)
+        .collect();
 
-    count
+    rucksacks
+        .chunks(group_size)
+        .map(common)
+        .collect()
 }
- 
-fn find_match(line1: &str, line2: &str) -> char {
-    assert_eq!(line1.len(), line2.len(), "Rucksacks must be of the same size");
-    assert!(line1.is_ascii(), "Invalid format");
-    assert!(line2.is_ascii(), "Invalid format");
-
-    for c in line1.chars() {
-        if line2.contains(c) {
-            return c;
-        }
-    }
 
-    panic!("No match found");
+fn common(sets: &[ItemSet]) -> ItemSet {
+    sets.iter().fold(ItemSet::ALL, |shared, set| shared & *set)
 }
 
-fn find_badge(group: &str) -> char {
-    assert!(group.is_ascii(), "Invalid format");
+// Lists the shared items of every rucksack and group, `--report shared [compartments] [group size]`
+fn print_shared(source: &str, args: &[String]) -> Result<(), String> {
+    let number = |index: usize, default: usize| match args.get(index) {
+        Some(arg) => arg.parse::<usize>().ok().filter(|n| *n > 0).ok_or_else(|| format!("Expected a positive number, got '{}'.", arg)),
+        None => Ok(default)
+    };
 
-    let mut lines = group.lines();
+    let compartments = number(0, 2)?;
+    let group_size = number(1, 3)?;
 
-    let line1 = lines.next().unwrap();
-    let line2 = lines.next().unwrap();
-    let line3 = lines.next().unwrap();
+    let describe = |set: ItemSet| if set.is_empty() { "none".to_string() } else { set.items().collect() };
+
+    println!("Shared between {} compartments:", compartments);
+    for (index, line) in source.lines().enumerate() {
+        println!("  rucksack {:>4}: {}", index + 1, describe(shared_in_compartments(line, compartments)));
+    }
 
-    for c in line1.chars() {
-        if line2.contains(c) && line3.contains(c) {
-            return c;
-        }
+    println!("Shared in groups of {}:", group_size);
+    for (index, shared) in shared_in_groups(source, group_size).into_iter().enumerate() {
+        println!("  group {:>4}: {}", index + 1, describe(shared));
     }
 
-    panic!("No badge found");
+    Ok(())
 }
 
 fn priority(character: char) -> u64 {
@@ -87,4 +152,12 @@ fn priority(character: char) -> u64 {
         character @ 'A'..='Z' => character as u64 - 38,
         _ => panic!("Invalid character.")
     }
-}
\ No newline at end of file
+}
+
+fn item(priority: u64) -> char {
+    match priority {
+        1..=26 => (priority as u8 + 96) as char,
+        27..=52 => (priority as u8 + 38) as char,
+        _ => panic!("Invalid priority.")
+    }
+}
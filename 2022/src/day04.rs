@@ -1,13 +1,137 @@
+use std::fmt::{self, Display, Formatter};
+use adventofcode_2022::runner::{Day, Solution};
+
+// Sorted, disjoint and non-adjacent inclusive ranges of sections
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct IntervalSet {
+    ranges: Vec<[usize; 2]>
+}
+
+impl IntervalSet {
+    fn new(start: usize, end: usize) -> Self {
+        assert!(start <= end, "Ranges can't end before they start.");
+        Self { ranges: vec![[start, end]] }
+    }
+
+    fn from_ranges(ranges: impl IntoIterator<Item = [usize; 2]>) -> Self {
+        let mut ranges: Vec<[usize; 2]> = ranges.into_iter().collect();
+        ranges.sort();
+
+        let mut merged: Vec<[usize; 2]> = vec![];
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range[0] <= last[1] + 1 => last[1] = last[1].max(range[1]),
+                _ => merged.push(range)
+            }
+        }
+
+        Self { ranges: merged }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    fn union(&self, other: &IntervalSet) -> IntervalSet {
+        IntervalSet::from_ranges(self.ranges.iter().chain(&other.ranges).copied())
+    }
+
+    fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut result = vec![];
+        let (mut i, mut j) = (0, 0);
+
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let a = self.ranges[i];
+            let b = other.ranges[j];
+
+            let start = a[0].max(b[0]);
+            let end = a[1].min(b[1]);
+
+            if start <= end {
+                result.push([start, end]);
+            }
+
+            if a[1] < b[1] { i += 1 } else { j += 1 }
+        }
+
+        IntervalSet { ranges: result }
+    }
+
+    fn difference(&self, other: &IntervalSet) -> IntervalSet {
+        let mut result = vec![];
+
+        for &[start, end] in &self.ranges {
+            let mut current = start;
+
+            for &[other_start, other_end] in &other.ranges {
+                if other_end < current || other_start > end {
+                    continue;
+                }
+
+                if other_start > current {
+                    result.push([current, other_start - 1]);
+                }
+
+                current = other_end + 1;
+                if current > end {
+                    break;
+                }
+            }
+
+            if current <= end {
+                result.push([current, end]);
+            }
+        }
+
+        IntervalSet { ranges: result }
+    }
+
+    // Whether every section of `other` is also in this set
+    fn contains(&self, other: &IntervalSet) -> bool {
+        other.difference(self).is_empty()
+    }
+
+    fn contains_section(&self, section: usize) -> bool {
+        self.ranges.iter().any(|range| range[0] <= section && section <= range[1])
+    }
+
+    // Total number of sections covered
+    fn coverage(&self) -> usize {
+        self.ranges.iter().map(|range| range[1] - range[0] + 1).sum()
+    }
+}
+
+impl Display for IntervalSet {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(fmt, "none");
+        }
+
+        let ranges: Vec<String> = self.ranges
+            .iter()
+            .map(|range| if range[0] == range[1] { range[0].to_string() } else { format!("{}-{}", range[0], range[1]) })
+            .collect();
+
+        write!(fmt, "{}", ranges.join(", "))
+    }
+}
+
+struct Sweep {
+    max_elves: usize,
+    busiest: IntervalSet,
+    uncovered: IntervalSet
+}
+
 fn main() {
     let input = include_str!("../input/day04.input");
 
-    // Part one
-    let full_overlaps = first_puzzle(input);
-    println!("There are {} assignment pairs where one range fully contains the other.", full_overlaps);
-
-    // Part two
-    let partial_overlaps = second_puzzle(input);
-    println!("There are {} assignment pairs where the ranges overlap.", partial_overlaps);
+    Day::new("day04", &[
+        "There are {} assignment pairs where one range fully contains the other.",
+        "There are {} assignment pairs where the ranges overlap.",
+    ])
+    .solution(Solution::new("raquel").part(first_puzzle).part(second_puzzle))
+    .report("sweep", print_sweep)
+    .run(input);
 }
 
 #[test]
@@ -18,6 +142,28 @@ fn sample() {
     assert_eq!(second_puzzle(sample), 4);
 }
 
+#[test]
+fn interval_sets() {
+    let a = IntervalSet::from_ranges([[1, 3], [5, 8], [4, 4], [12, 15]]);
+    let b = IntervalSet::from_ranges([[3, 6], [14, 20]]);
+
+    assert_eq!(a.ranges, vec![[1, 8], [12, 15]]);
+    assert_eq!(a.union(&b).ranges, vec![[1, 8], [12, 20]]);
+    assert_eq!(a.intersection(&b).ranges, vec![[3, 6], [14, 15]]);
+    assert_eq!(a.difference(&b).ranges, vec![[1, 2], [7, 8], [12, 13]]);
+    assert_eq!(b.difference(&a).ranges, vec![[16, 20]]);
+    assert_eq!(a.coverage(), 12);
+
+    assert!(a.contains(&IntervalSet::new(2, 7)));
+    assert!(!a.contains(&IntervalSet::new(7, 12)));
+    assert!(a.contains_section(13) && !a.contains_section(10));
+
+    let sweep = sweep(&parse_assignments(include_str!("../sample/day04.input")));
+    assert_eq!(sweep.max_elves, 8);
+    assert_eq!(sweep.busiest.to_string(), "6");
+    assert!(sweep.uncovered.is_empty());
+}
+
 fn first_puzzle(source: &str) -> usize {
     source
         .lines()
@@ -34,9 +180,9 @@ fn second_puzzle(source: &str) -> usize {
         .count()
 }
 
-fn get_ranges(source: &str) -> ([usize; 2], [usize; 2]) {
+fn get_ranges(source: &str) -> (IntervalSet, IntervalSet) {
     let mut ranges_str = source.split(',');
-    
+
     let mut range1 = ranges_str.next().unwrap().split('-');
     let mut range2 = ranges_str.next().unwrap().split('-');
 
@@ -46,14 +192,91 @@ fn get_ranges(source: &str) -> ([usize; 2], [usize; 2]) {
     let start2 = range2.next().unwrap().parse::<usize>().unwrap();
     let end2 = range2.next().unwrap().parse::<usize>().unwrap();
 
-    ([start1, end1], [start2, end2])
+    (IntervalSet::new(start1, end1), IntervalSet::new(start2, end2))
 }
 
-fn full_overlap(range1: &[usize; 2], range2: &[usize; 2]) -> bool {
-    (range1[0] <= range2[0] && range1[1] >= range2[1]) ||
-    (range2[0] <= range1[0] && range2[1] >= range1[1])
+fn parse_assignments(source: &str) -> Vec<IntervalSet> {
+    source
+        .lines()
+        .map(get_ranges)
+        .flat_map(|(range1, range2)| [range1, range2])
+        .collect()
+}
+
+fn full_overlap(range1: &IntervalSet, range2: &IntervalSet) -> bool {
+    range1.contains(range2) || range2.contains(range1)
+}
+
+fn partial_overlap(range1: &IntervalSet, range2: &IntervalSet) -> bool {
+    !range1.intersection(range2).is_empty()
 }
 
-fn partial_overlap(range1: &[usize; 2], range2: &[usize; 2]) -> bool {
-    range1[1] >= range2[0] && range2[1] >= range1[0]
-}
\ No newline at end of file
+// Sweeps over the start and end of every assignment to find the sections covered by the most elves,
+// and the sections between the lowest and highest assigned one that nobody covers
+fn sweep(assignments: &[IntervalSet]) -> Sweep {
+    let mut events: Vec<(usize, isize)> = assignments
+        .iter()
+        .flat_map(|assignment| assignment.ranges.iter().flat_map(|range| [(range[0], 1), (range[1] + 1, -1)]))
+        .collect();
+
+    // Ends are stored one past the last section they cover
+    events.sort();
+
+    let mut max_elves = 0;
+    let mut busiest = vec![];
+    let mut elves = 0;
+
+    for (index, &(section, change)) in events.iter().enumerate() {
+        elves += change;
+
+        // Only look at the count once every event at this section is applied
+        let next = events.get(index + 1).map(|(next, _)| *next);
+        if next == Some(section) {
+            continue;
+        }
+
+        let elves = elves as usize;
+        let until = next.unwrap_or(section);
+
+        if elves > max_elves {
+            max_elves = elves;
+            busiest.clear();
+        }
+
+        if elves == max_elves && elves > 0 && until > section {
+            busiest.push([section, until - 1]);
+        }
+    }
+
+    let covered = assignments.iter().fold(IntervalSet::default(), |covered, assignment| covered.union(assignment));
+    let uncovered = match (covered.ranges.first(), covered.ranges.last()) {
+        (Some(first), Some(last)) => IntervalSet::new(first[0], last[1]).difference(&covered),
+        _ => IntervalSet::default()
+    };
+
+    Sweep {
+        max_elves,
+        busiest: IntervalSet::from_ranges(busiest),
+        uncovered
+    }
+}
+
+// `--report sweep [section...]` also tells how many elves cover each of the given sections
+fn print_sweep(source: &str, args: &[String]) -> Result<(), String> {
+    let assignments = parse_assignments(source);
+    let sweep = sweep(&assignments);
+    let covered = assignments.iter().fold(IntervalSet::default(), |covered, assignment| covered.union(assignment));
+
+    println!("{} assignments cover {} sections: {}", assignments.len(), covered.coverage(), covered);
+    println!("At most {} elves cover the same section, at sections {}", sweep.max_elves, sweep.busiest);
+    println!("Sections nobody covers: {}", sweep.uncovered);
+
+    for section in args {
+        let section = section.parse::<usize>().map_err(|_| format!("Invalid section '{}'.", section))?;
+        let elves = assignments.iter().filter(|assignment| assignment.contains_section(section)).count();
+
+        println!("Section {} is covered by {} elves", section, elves);
+    }
+
+    Ok(())
+}
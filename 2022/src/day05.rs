@@ -1,19 +1,115 @@
+use std::{collections::{HashMap, VecDeque}, fmt::{self, Display}, fs, hash::Hash};
+use adventofcode_2022::runner::{Day, Solution};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 struct Move {
     amount: usize,
     from: usize,
     to: usize
 }
 
+impl Display for Move {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "move {} from {} to {}", self.amount, self.from, self.to)
+    }
+}
+
+type Crate = String;
+
+// The stacks along with the crane that is about to move them, for planning
+type State<C> = (Vec<Vec<Crate>>, C);
+
+trait Crane {
+    fn execute(&mut self, command: &Move, stacks: &mut [Vec<Crate>]);
+}
+
+// Moves crates one at a time
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct CrateMover9000;
+
+// Moves all the crates of a command at once, keeping their order
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct CrateMover9001;
+
+// Can only lift so many crates at once, so larger moves are split into several lifts
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct LimitedCrane {
+    capacity: usize
+}
+
+// Moves all the crates of a command at once, but every other command flips the batch over.
+// Only whether the next batch gets flipped matters, so planning doesn't tell apart states that
+// only differ by how many commands it took to reach them.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+struct AlternatingCrane {
+    flipped: bool
+}
+
+// Any of the crane models, as picked on the command line
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum Model {
+    Mover9000(CrateMover9000),
+    Mover9001(CrateMover9001),
+    Limited(LimitedCrane),
+    Alternating(AlternatingCrane)
+}
+
+impl Crane for Model {
+    fn execute(&mut self, command: &Move, stacks: &mut [Vec<Crate>]) {
+        match self {
+            Model::Mover9000(crane) => crane.execute(command, stacks),
+            Model::Mover9001(crane) => crane.execute(command, stacks),
+            Model::Limited(crane) => crane.execute(command, stacks),
+            Model::Alternating(crane) => crane.execute(command, stacks)
+        }
+    }
+}
+
+impl Crane for CrateMover9000 {
+    fn execute(&mut self, command: &Move, stacks: &mut [Vec<Crate>]) {
+        for _ in 0..command.amount {
+            lift(command.from, command.to, 1, false, stacks);
+        }
+    }
+}
+
+impl Crane for CrateMover9001 {
+    fn execute(&mut self, command: &Move, stacks: &mut [Vec<Crate>]) {
+        lift(command.from, command.to, command.amount, false, stacks);
+    }
+}
+
+impl Crane for LimitedCrane {
+    fn execute(&mut self, command: &Move, stacks: &mut [Vec<Crate>]) {
+        let mut left = command.amount;
+
+        while left > 0 {
+            let amount = left.min(self.capacity);
+            lift(command.from, command.to, amount, false, stacks);
+            left -= amount;
+        }
+    }
+}
+
+impl Crane for AlternatingCrane {
+    fn execute(&mut self, command: &Move, stacks: &mut [Vec<Crate>]) {
+        lift(command.from, command.to, command.amount, self.flipped, stacks);
+        self.flipped = !self.flipped;
+    }
+}
+
 fn main() {
     let input = include_str!("../input/day05.input");
 
-    // Part one
-    let word9000 = first_puzzle(input);
-    println!("The word formed by the stacks (CrateMover 9000) is {}.", word9000);
-
-    // Part two
-    let word9001 = second_puzzle(input);
-    println!("The word formed by the stacks (CrateMover 9001) is {}.", word9001);
+    Day::new("day05", &[
+        "The word formed by the stacks (CrateMover 9000) is {}.",
+        "The word formed by the stacks (CrateMover 9001) is {}.",
+    ])
+    .solution(Solution::new("lotus").part(first_puzzle).part(second_puzzle))
+    .report("crane", print_crane)
+    .report("stacks", print_stacks)
+    .report("plan", print_plan)
+    .run(input);
 }
 
 #[test]
@@ -24,72 +120,343 @@ fn sample() {
     assert_eq!(second_puzzle(sample), "MCD");
 }
 
+#[test]
+fn cranes() {
+    let sample = include_str!("../sample/day05.input");
+
+    // The two existing models are the extremes of a limited crane
+    assert_eq!(solve(sample, LimitedCrane { capacity: 1 }), "CMZ");
+    assert_eq!(solve(sample, LimitedCrane { capacity: 3 }), "MCD");
+    assert_eq!(solve(sample, LimitedCrane { capacity: 2 }), "MCZ");
+    assert_eq!(solve(sample, AlternatingCrane::default()), "MCZ");
+}
+
+#[test]
+fn drawings() {
+    let sample = include_str!("../sample/day05.input");
+    let (drawing, _) = sample.split_once("\n\n").unwrap();
+
+    let stacks = parse_drawing(drawing);
+    assert_eq!(stacks, vec![vec!["Z", "N"], vec!["M", "C", "D"], vec!["P"]]);
+    assert_eq!(render(&stacks), drawing);
+
+    // Without the trailing spaces
+    let trimmed: Vec<&str> = drawing.lines().map(str::trim_end).collect();
+    assert_eq!(parse_drawing(&trimmed.join("\n")), stacks);
+
+    // Wider labels and more than nine stacks
+    let mut wide: Vec<Vec<Crate>> = (1..=11).map(|stack| vec![format!("S{}", stack)]).collect();
+    wide[0].push("TOP".to_string());
+    wide[4].clear();
+
+    let drawing = render(&wide);
+    assert!(drawing.starts_with("[TOP]"));
+    assert_eq!(parse_drawing(&drawing), wide);
+}
+
+#[test]
+fn planning() {
+    let sample = include_str!("../sample/day05.input");
+    let (start, _) = parse_input(sample);
+
+    // The sample's own four moves are not the shortest way to get there
+    let target = parse_drawing("        [Z]\n        [N]\n        [D]\n[C] [M] [P]\n 1   2   3 ");
+    let steps = plan(&start, &target, CrateMover9000).unwrap();
+    assert_eq!(steps.len(), 3);
+
+    // The plan reads back as commands that do reach the target
+    let mut stacks = start.clone();
+    for step in &steps {
+        CrateMover9000.execute(&parse_command(&step.to_string()), &mut stacks);
+    }
+    assert_eq!(stacks, target);
+
+    let flipped = parse_drawing("[Z]\n[N] [M]\n 1   2 ");
+    let stacked = parse_drawing("[Z]\n[M]\n[N]\n 1   2 ");
+    assert_eq!(plan(&flipped, &stacked, CrateMover9001).unwrap().len(), 2);
+    assert_eq!(plan(&flipped, &stacked, LimitedCrane { capacity: 2 }).unwrap().len(), 2);
+    assert_eq!(plan(&flipped, &stacked, AlternatingCrane::default()).unwrap().len(), 3);
+    assert!(plan(&flipped, &parse_drawing("[Z]\n 1   2 "), CrateMover9000).is_none());
+    assert!(plan(&flipped, &flipped, CrateMover9000).unwrap().is_empty());
+    assert_eq!(plan(&flipped, &stacked, crane(&["alternating".to_string()]).unwrap()).unwrap().len(), 3);
+    assert!(crane(&["limited".to_string()]).is_err());
+    assert!(crane(&["9002".to_string()]).is_err());
+}
+
+#[test]
+fn crane_states() {
+    let (start, _) = parse_input(include_str!("../sample/day05.input"));
+
+    // Moving a crate there and back again leads to the same planning state two moves deeper
+    let mut visited: std::collections::HashSet<State<AlternatingCrane>> = Default::default();
+    let mut state = (start.clone(), AlternatingCrane::default());
+    visited.insert(state.clone());
+
+    for step in [Move { amount: 1, from: 1, to: 2 }, Move { amount: 1, from: 2, to: 1 }] {
+        state.1.execute(&step, &mut state.0);
+        visited.insert(state.clone());
+    }
+
+    assert_eq!(state.0, start);
+    assert_eq!(visited.len(), 2);
+}
+
 fn first_puzzle(source: &str) -> String {
-    solve(source, false)
+    solve(source, CrateMover9000)
 }
 
 fn second_puzzle(source: &str) -> String {
-    solve(source, true)
+    solve(source, CrateMover9001)
 }
 
-fn solve(source: &str, upgraded: bool) -> String {
-    let mut parts = source.split("\n\n").peekable();
+fn solve<C: Crane>(source: &str, mut crane: C) -> String {
+    let (mut stacks, commands) = parse_input(source);
 
-    let stacks_num = (parts.peek().unwrap().len() + 1) / 4;
-    let mut stacks = vec![vec![]; stacks_num];
+    for command in &commands {
+        crane.execute(command, &mut stacks);
+    }
 
-    parts
-        .next()
-        .unwrap()
-        .lines()
-        .rev()
-        .filter(|line| !line.starts_with(" 1"))
-        .for_each(|row| {
-            let crates_len = (row.len() + 1) / 4;
-
-            for i in 0..crates_len {
-                let correction = if i > 0 {1} else {0};
-                let slice = &row[(i*(3 + correction))..(i*(3 + correction) + 3)];
-
-                if slice.starts_with('[') && slice.ends_with(']') {
-                    let letter = &slice[1..slice.len() - 1].parse::<char>().unwrap();
-                    stacks[i].push(*letter);
-                } 
-            }
-        });
+    top_word(&stacks)
+}
 
-    parts
-        .next()
-        .unwrap()
+fn parse_input(source: &str) -> (Vec<Vec<Crate>>, Vec<Move>) {
+    let (drawing, commands) = source.split_once("\n\n").expect("Invalid format.");
+
+    let commands = commands
         .lines()
+        .filter(|line| !line.is_empty())
         .map(parse_command)
-        .for_each(|command| execute_command(command, &mut stacks, upgraded));
+        .collect();
+
+    (parse_drawing(drawing), commands)
+}
+
+// Every crate goes to the stack whose number sits closest to it, so labels can be of any width
+// and rows don't need to be padded with trailing spaces
+fn parse_drawing(drawing: &str) -> Vec<Vec<Crate>> {
+    let mut rows: Vec<&str> = drawing
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+
+    let numbers = words(rows.pop().expect("Expected the stack numbers."));
+
+    for (index, (_, number)) in numbers.iter().enumerate() {
+        assert_eq!(number.parse::<usize>().ok(), Some(index + 1), "Stacks must be numbered from 1.");
+    }
+
+    let mut stacks = vec![vec![]; numbers.len()];
 
-    let mut word = String::default();
+    for row in rows.iter().rev() {
+        for (center, word) in words(row) {
+            let label = word
+                .strip_prefix('[')
+                .and_then(|word| word.strip_suffix(']'))
+                .expect("Crates must be drawn as [label].");
 
-    for stack in stacks {
-        if stack.first().is_some() {
-            word.push(*stack.last().unwrap());
+            let (stack, _) = numbers
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, (number_center, _))| number_center.abs_diff(center))
+                .unwrap();
+
+            stacks[stack].push(label.to_string());
         }
     }
-    
-    word
+
+    stacks
 }
 
-fn execute_command(command: Move, stacks: &mut [Vec<char>], upgraded: bool) {
-    if !upgraded {
-        for _ in 0..command.amount {
-            let letter = stacks[command.from - 1].pop().unwrap();
-            stacks[command.to - 1].push(letter);
+// The words of a line along with their center, counted in half characters
+fn words(line: &str) -> Vec<(usize, &str)> {
+    let mut words = vec![];
+    let mut start = None;
+
+    for (index, character) in line.char_indices().chain([(line.len(), ' ')]) {
+        match (start, character.is_whitespace()) {
+            (None, false) => start = Some(index),
+            (Some(first), true) => {
+                words.push((first + index - 1, &line[first..index]));
+                start = None;
+            }
+            _ => ()
         }
-    } else  {
-        let from = &mut stacks[command.from - 1];
-        let mut letters: Vec<char> = from
-            .drain((from.len() - command.amount)..)
+    }
+
+    words
+}
+
+// Draws the stacks the same way the puzzle does, every column as wide as the widest crate
+fn render(stacks: &[Vec<Crate>]) -> String {
+    let width = stacks
+        .iter()
+        .flatten()
+        .map(|label| label.len() + 2)
+        .chain([3, stacks.len().to_string().len()])
+        .max()
+        .unwrap();
+
+    let height = stacks.iter().map(Vec::len).max().unwrap_or(0);
+    let mut lines = vec![];
+
+    for level in (0..height).rev() {
+        let cells: Vec<String> = stacks
+            .iter()
+            .map(|stack| match stack.get(level) {
+                Some(label) => format!("{:^width$}", format!("[{}]", label)),
+                None => " ".repeat(width)
+            })
             .collect();
-        
-        stacks[command.to - 1].append(&mut letters);
+
+        lines.push(cells.join(" "));
+    }
+
+    let numbers: Vec<String> = (1..=stacks.len())
+        .map(|number| format!("{:^width$}", number))
+        .collect();
+
+    lines.push(numbers.join(" "));
+    lines.join("\n")
+}
+
+fn top_word(stacks: &[Vec<Crate>]) -> String {
+    stacks
+        .iter()
+        .filter_map(|stack| stack.last())
+        .map(String::as_str)
+        .collect()
+}
+
+// Moves the top `amount` crates between two stacks (numbered from 1) in a single lift
+fn lift(from: usize, to: usize, amount: usize, reversed: bool, stacks: &mut [Vec<Crate>]) {
+    let from = &mut stacks[from - 1];
+    assert!(from.len() >= amount, "Tried to move more crates than the stack has.");
+
+    let mut letters: Vec<Crate> = from
+        .drain((from.len() - amount)..)
+        .collect();
+
+    if reversed {
+        letters.reverse();
+    }
+
+    stacks[to - 1].append(&mut letters);
+}
+
+// Breadth-first search for one of the shortest move lists that rearranges `start` into `target`.
+// Cranes that keep some state between moves are part of the searched state.
+fn plan<C: Crane + Clone + Eq + Hash>(start: &[Vec<Crate>], target: &[Vec<Crate>], crane: C) -> Option<Vec<Move>> {
+    let mut crates: Vec<&Crate> = start.iter().flatten().collect();
+    let mut wanted: Vec<&Crate> = target.iter().flatten().collect();
+    crates.sort();
+    wanted.sort();
+
+    if start.len() != target.len() || crates != wanted {
+        return None;
     }
+
+    let start = (start.to_vec(), crane);
+    let mut previous: HashMap<State<C>, Option<(State<C>, Move)>> = HashMap::new();
+    let mut queue = VecDeque::new();
+
+    previous.insert(start.clone(), None);
+    queue.push_back(start);
+
+    while let Some(state) = queue.pop_front() {
+        if state.0 == target {
+            let mut steps = vec![];
+            let mut current = &state;
+
+            while let Some((parent, step)) = &previous[current] {
+                steps.push(*step);
+                current = parent;
+            }
+
+            steps.reverse();
+            return Some(steps);
+        }
+
+        let (stacks, crane) = &state;
+
+        for from in 0..stacks.len() {
+            for to in (0..stacks.len()).filter(|to| *to != from) {
+                for amount in 1..=stacks[from].len() {
+                    let step = Move { amount, from: from + 1, to: to + 1 };
+                    let mut next = state.clone();
+                    next.1.execute(&step, &mut next.0);
+
+                    if !previous.contains_key(&next) {
+                        previous.insert(next.clone(), Some(((stacks.clone(), crane.clone()), step)));
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+    }
+
+    None
+}
+
+// Picks a crane model from `9000`, `9001`, `limited <capacity>` or `alternating`
+fn crane(args: &[String]) -> Result<Model, String> {
+    match args.first().map(|arg| arg.as_str()) {
+        Some("9000") | None => Ok(Model::Mover9000(CrateMover9000)),
+        Some("9001") => Ok(Model::Mover9001(CrateMover9001)),
+        Some("limited") => {
+            let capacity = args
+                .get(1)
+                .and_then(|capacity| capacity.parse::<usize>().ok())
+                .filter(|capacity| *capacity > 0)
+                .ok_or("Expected the crane's capacity.")?;
+
+            Ok(Model::Limited(LimitedCrane { capacity }))
+        }
+        Some("alternating") => Ok(Model::Alternating(AlternatingCrane::default())),
+        Some(other) => Err(format!("Unknown crane model '{}'.", other))
+    }
+}
+
+// `--report crane [model]`
+fn print_crane(source: &str, args: &[String]) -> Result<(), String> {
+    println!("The word formed by the stacks is {}.", solve(source, crane(args)?));
+    Ok(())
+}
+
+// Draws the stacks after the first few moves, `--report stacks [moves] [model]`
+fn print_stacks(source: &str, args: &[String]) -> Result<(), String> {
+    let (mut stacks, commands) = parse_input(source);
+
+    let moves = match args.first() {
+        Some(moves) => moves.parse::<usize>().map_err(|_| format!("Invalid number of moves '{}'.", moves))?,
+        None => commands.len()
+    };
+
+    let mut crane = crane(args.get(1..).unwrap_or_default())?;
+
+    for command in commands.iter().take(moves) {
+        crane.execute(command, &mut stacks);
+    }
+
+    println!("{}", render(&stacks));
+    println!();
+    println!("After {} of {} moves the top crates spell {}.", moves.min(commands.len()), commands.len(), top_word(&stacks));
+    Ok(())
+}
+
+// Plans how to reach the drawing in the given file, `--report plan <file> [model]`
+fn print_plan(source: &str, args: &[String]) -> Result<(), String> {
+    let (start, _) = parse_input(source);
+    let path = args.first().ok_or("Expected a file with the target drawing.")?;
+    let target = parse_drawing(&fs::read_to_string(path).map_err(|error| format!("Could not read {}: {}", path, error))?);
+
+    let steps = plan(&start, &target, crane(args.get(1..).unwrap_or_default())?);
+
+    match steps {
+        Some(steps) => steps.iter().for_each(|step| println!("{}", step)),
+        None => println!("The target can't be reached with the same crates.")
+    }
+
+    Ok(())
 }
 
 fn parse_command(command_str: &str) -> Move {
@@ -1,16 +1,74 @@
 #![allow(unused_imports)]
-use std::{collections::VecDeque, iter::zip};
+use std::{collections::VecDeque, io::{self, BufReader, Read}, iter::zip};
+use adventofcode_2022::runner::{Day, Solution};
+
+// Slides windows of several sizes over a stream at once, in constant time per byte and window
+struct Detector {
+    sizes: Vec<usize>,
+    // How many times each byte appears in every window
+    counts: Vec<[usize; 256]>,
+    distinct: Vec<usize>,
+    // The last bytes read, as many as the largest window needs
+    recent: VecDeque<u8>,
+    read: usize
+}
+
+impl Detector {
+    fn new(sizes: &[usize]) -> Self {
+        assert!(sizes.iter().all(|size| *size > 0), "Markers can't be empty.");
+
+        Self {
+            sizes: sizes.to_vec(),
+            counts: vec![[0; 256]; sizes.len()],
+            distinct: vec![0; sizes.len()],
+            recent: VecDeque::with_capacity(sizes.iter().max().copied().unwrap_or(0) + 1),
+            read: 0
+        }
+    }
+
+    // Takes in one more byte and tells, for every window, whether it now holds a marker
+    fn push(&mut self, byte: u8) -> impl Iterator<Item = bool> + '_ {
+        self.recent.push_front(byte);
+        self.read += 1;
+
+        for (index, &size) in self.sizes.iter().enumerate() {
+            let counts = &mut self.counts[index];
+
+            counts[byte as usize] += 1;
+            if counts[byte as usize] == 1 {
+                self.distinct[index] += 1;
+            }
+
+            // The byte that just slid out of this window
+            if let Some(&old) = self.recent.get(size) {
+                counts[old as usize] -= 1;
+                if counts[old as usize] == 0 {
+                    self.distinct[index] -= 1;
+                }
+            }
+        }
+
+        if self.recent.len() > self.sizes.iter().max().copied().unwrap_or(0) {
+            self.recent.pop_back();
+        }
+
+        self.sizes
+            .iter()
+            .zip(&self.distinct)
+            .map(|(size, distinct)| distinct == size)
+    }
+}
 
 fn main() {
     let input = include_str!("../input/day06.input");
 
-    // Part one
-    let first_packet_marker = first_puzzle(input);
-    println!("First start-of-packet marker detected after {} characters.", first_packet_marker);
-
-    // Part two
-    let first_message_marker = second_puzzle(input);
-    println!("First start-of-message marker detected after {} characters.", first_message_marker);
+    Day::new("day06", &[
+        "First start-of-packet marker detected after {} characters.",
+        "First start-of-message marker detected after {} characters.",
+    ])
+    .solution(Solution::new("fischl").optional_part(first_puzzle, "No start-of-packet marker in the datastream.").optional_part(second_puzzle, "No start-of-message marker in the datastream."))
+    .report("markers", print_markers)
+    .run(input);
 }
 
 #[test]
@@ -22,42 +80,116 @@ fn sample() {
 
     zip(results.iter(), lines)
         .for_each(|(results, line)| {
-            assert_eq!(first_puzzle(line), results.0);
-            assert_eq!(second_puzzle(line), results.1);
+            assert_eq!(first_puzzle(line), Some(results.0));
+            assert_eq!(second_puzzle(line), Some(results.1));
         });
 }
 
-fn first_puzzle(source: &str) -> usize {
+#[test]
+fn detector() {
+    let sample = include_str!("../sample/day06.input");
+
+    // Both markers of the first line are found in a single pass
+    let found = markers(sample.lines().next().unwrap().as_bytes(), &[4, 14]).unwrap();
+    assert_eq!(found[0][0], 7);
+    assert_eq!(found[1][0], 19);
+    assert!(found[0].windows(2).all(|pair| pair[0] < pair[1]));
+
+    assert_eq!(markers("abcd".as_bytes(), &[1, 2, 4, 5]).unwrap(), vec![vec![1, 2, 3, 4], vec![2, 3, 4], vec![4], vec![]]);
+    assert_eq!(markers("abac\nad".as_bytes(), &[2, 3]).unwrap(), vec![vec![2, 3, 4, 5, 6], vec![4, 6]]);
+
+    assert_eq!(first_marker("aaaaaaa".as_bytes(), 2).unwrap(), None);
+    assert_eq!(first_marker("".as_bytes(), 1).unwrap(), None);
+
+    // A stream without markers has no answer rather than a panic
+    assert_eq!(first_puzzle("abcabcabc"), None);
+    assert_eq!(second_puzzle("abcd"), None);
+    assert_eq!(second_puzzle(""), None);
+}
+
+fn first_puzzle(source: &str) -> Option<usize> {
     solve(source, 4)
 }
 
-fn second_puzzle(source: &str) -> usize {
+fn second_puzzle(source: &str) -> Option<usize> {
     solve(source, 14)
 }
 
-fn solve(source: &str, marker_size: usize) -> usize {
-    let mut different = VecDeque::with_capacity(4);
+fn solve(source: &str, marker_size: usize) -> Option<usize> {
+    first_marker(source.as_bytes(), marker_size).expect("Could not read the datastream.")
+}
 
-    for (index, character) in source.chars().enumerate() {
-        if different.contains(&character) {
-            loop {
-                let c = different.pop_front().expect("No more elements in queue.");
+// How many characters are read before the first marker of the given size, if there is one
+fn first_marker<R: Read>(reader: R, marker_size: usize) -> io::Result<Option<usize>> {
+    let mut detector = Detector::new(&[marker_size]);
 
-                if c == character {
-                    break;
-                }
-            }
+    for byte in signal(reader) {
+        if detector.push(byte?).any(|found| found) {
+            return Ok(Some(detector.read));
+        }
+    }
 
-            different.push_back(character);
-            continue;
-        } else {
-            different.push_back(character);
+    Ok(None)
+}
+
+// Every position, counted in characters read, where a marker of each size ends
+fn markers<R: Read>(reader: R, sizes: &[usize]) -> io::Result<Vec<Vec<usize>>> {
+    let mut detector = Detector::new(sizes);
+    let mut found = vec![vec![]; sizes.len()];
+
+    for byte in signal(reader) {
+        let byte = byte?;
+        let read = detector.read + 1;
+
+        for (positions, marker) in found.iter_mut().zip(detector.push(byte)) {
+            if marker {
+                positions.push(read);
+            }
         }
+    }
 
-        if different.len() == marker_size {
-            return index + 1;
+    Ok(found)
+}
+
+// Line breaks are not part of the datastream
+fn signal<R: Read>(reader: R) -> impl Iterator<Item = io::Result<u8>> {
+    BufReader::new(reader)
+        .bytes()
+        .filter(|byte| !matches!(byte, Ok(b'\n' | b'\r')))
+}
+
+// Lists where markers of every size end, `--report markers [size...]`. Reads stdin when the input is `-`.
+fn print_markers(source: &str, args: &[String]) -> Result<(), String> {
+    let (stdin, args) = match args.first().map(|arg| arg.as_str()) {
+        Some("-") => (true, &args[1..]),
+        _ => (false, args)
+    };
+
+    let mut sizes: Vec<usize> = args
+        .iter()
+        .map(|size| size.parse::<usize>().ok().filter(|size| *size > 0).ok_or_else(|| format!("Invalid marker size '{}'.", size)))
+        .collect::<Result<_, _>>()?;
+
+    if sizes.is_empty() {
+        sizes = vec![4, 14];
+    }
+
+    let found = if stdin {
+        markers(io::stdin().lock(), &sizes)
+    } else {
+        markers(source.as_bytes(), &sizes)
+    }
+    .map_err(|error| format!("Could not read the datastream: {}", error))?;
+
+    for (size, positions) in sizes.iter().zip(&found) {
+        let shown: Vec<String> = positions.iter().take(10).map(usize::to_string).collect();
+        let more = if positions.len() > shown.len() { ", ..." } else { "" };
+
+        match positions.first() {
+            Some(first) => println!("Size {:>3}: {} markers, first after {} characters ({}{})", size, positions.len(), first, shown.join(", "), more),
+            None => println!("Size {:>3}: no markers", size)
         }
     }
 
-    0
-}
\ No newline at end of file
+    Ok(())
+}
@@ -1,10 +1,10 @@
-use std::fmt::{Display, Formatter, Result};
+use std::{collections::{HashMap, HashSet, VecDeque}, fmt::{self, Display, Formatter}, fs, io, path::Path};
+use adventofcode_2022::{profile::format_bytes, runner::{Day, Solution}};
 
 #[derive(Eq, PartialEq)]
 enum Command {
-    CdBack,
-    CdRoot,
-    CdDir(String),
+    // Absolute or relative, may go through several directories
+    Cd(String),
     Ls
 }
 
@@ -14,8 +14,27 @@ enum FsEntry {
     File(String, usize)
 }
 
+// Values that are looked up by name among their siblings
+trait Named {
+    fn name(&self) -> &str;
+}
+
+impl Named for FsEntry {
+    fn name(&self) -> &str {
+        match self {
+            Self::Directory(name) | Self::File(name, _) => name
+        }
+    }
+}
+
+impl FsEntry {
+    fn is_dir(&self) -> bool {
+        matches!(self, Self::Directory(_))
+    }
+}
+
 impl Display for FsEntry {
-    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
         match self {
             Self::Directory(name) => write!(fmt, "{} (dir)", name),
             Self::File(name, size) => write!(fmt, "{} (file, size={})", name, size),
@@ -23,6 +42,56 @@ impl Display for FsEntry {
     }
 }
 
+#[derive(Clone, Copy)]
+struct Disk {
+    total: usize,
+    // Free space needed for the update
+    required: usize
+}
+
+impl Disk {
+    const DEVICE: Disk = Disk { total: 70000000, required: 30000000 };
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Objective {
+    // Ties are broken by deleting fewer bytes
+    FewestDeletions,
+    // Ties are broken by deleting fewer directories
+    LeastBytes
+}
+
+// In which order a generated transcript visits the directories
+#[derive(Clone, Copy)]
+enum Traversal {
+    // Going into every directory right after listing its parent's, like the puzzle's own sessions
+    DepthFirst,
+    BreadthFirst
+}
+
+struct Cleanup {
+    disk: Disk,
+    objective: Objective,
+    // Nodes that must survive, along with everything above them
+    protected: Vec<usize>,
+    // How many search states to look at before settling for the best plan found so far
+    budget: usize
+}
+
+impl Cleanup {
+    fn new(disk: Disk, objective: Objective) -> Self {
+        Self { disk, objective, protected: vec![], budget: 1_000_000 }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Plan {
+    directories: Vec<usize>,
+    freed: usize,
+    // False when the search ran out of budget before proving nothing better exists
+    optimal: bool
+}
+
 #[derive(Debug)]
 struct Tree<T> where T: PartialEq {
     arena: Vec<Node<T>>,
@@ -33,72 +102,564 @@ impl<T> Tree<T> where T: PartialEq {
         Tree { arena: vec![] }
     }
 
+    // Children always come after their parent in the arena, so going through it backwards visits
+    // every node after all of its descendants
+    fn post_order(&self) -> impl Iterator<Item = usize> {
+        (0..self.arena.len()).rev()
+    }
+
+    // How far below the root every node is, in a single pass since parents come first
+    fn depths(&self) -> Vec<usize> {
+        let mut depths = vec![0; self.arena.len()];
+
+        for index in 0..self.arena.len() {
+            if let Some(parent) = self.arena[index].parent {
+                depths[index] = depths[parent] + 1;
+            }
+        }
+
+        depths
+    }
+
+    // Every node under `index` and itself, parents before their children
+    fn walk(&self, index: usize) -> Walk<'_, T> {
+        Walk { tree: self, stack: vec![(index, 0)] }
+    }
+
+    // The parent of `index`, its parent, and so on up to the root
+    fn ancestors(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        std::iter::successors(self.arena[index].parent, |index| self.arena[*index].parent)
+    }
+}
+
+impl<T> Tree<T> where T: PartialEq + Named {
     fn insert(&mut self, value: T, parent: Option<usize>) -> usize {
         let index = self.arena.len();
-        self.arena.push(Node::new(self.arena.len(), value, parent));
 
         if let Some(p) = parent {
             self.arena[p].children.push(index);
+            self.arena[p].by_name.insert(value.name().to_string(), index);
         }
 
+        self.arena.push(Node::new(value, parent));
         index
     }
 
-    fn find(&mut self, value: T, parent: Option<usize>) -> Option<usize> {
+    fn child(&self, parent: usize, name: &str) -> Option<usize> {
+        self.arena[parent].by_name.get(name).copied()
+    }
+}
+
+// Pre-order walk yielding every node with its depth below where the walk started
+struct Walk<'a, T> where T: PartialEq {
+    tree: &'a Tree<T>,
+    stack: Vec<(usize, usize)>
+}
+
+impl<'a, T> Iterator for Walk<'a, T> where T: PartialEq {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (index, depth) = self.stack.pop()?;
+
+        for child in self.tree.arena[index].children.iter().rev() {
+            self.stack.push((*child, depth + 1));
+        }
+
+        Some((index, depth))
+    }
+}
+
+impl Tree<FsEntry> {
+    const ROOT: usize = 0;
+
+    // Adds a listed entry to a directory. Listing the same directory again updates what's already there.
+    fn add(&mut self, directory: usize, entry: FsEntry) -> usize {
+        match self.child(directory, entry.name()) {
+            Some(existing) => {
+                let node = &mut self.arena[existing];
+                assert_eq!(node.value.is_dir(), entry.is_dir(), "{} is listed both as a file and as a directory.", entry.name());
+
+                if !entry.is_dir() {
+                    node.value = entry;
+                }
+
+                existing
+            }
+            None => self.insert(entry, Some(directory))
+        }
+    }
+
+    // Follows a path from the directory `from`, returns None if something along the way doesn't exist
+    fn resolve(&self, from: usize, path: &str) -> Option<usize> {
+        let mut index = if path.starts_with('/') { Self::ROOT } else { from };
+
+        for segment in path.split('/') {
+            index = match segment {
+                "" | "." => index,
+                ".." => self.arena[index].parent.unwrap_or(Self::ROOT),
+                name if self.arena[index].value.is_dir() => self.child(index, name)?,
+                _ => return None
+            };
+        }
+
+        Some(index)
+    }
+
+    // Like `resolve`, but creates the directories that haven't been listed yet
+    fn make_dirs(&mut self, from: usize, path: &str) -> usize {
+        let mut index = if path.starts_with('/') { Self::ROOT } else { from };
+
+        for segment in path.split('/') {
+            index = match segment {
+                "" | "." => index,
+                ".." => self.arena[index].parent.unwrap_or(Self::ROOT),
+                name => self.add(index, FsEntry::Directory(name.to_string()))
+            };
+        }
+
+        index
+    }
+
+    fn path(&self, index: usize) -> String {
+        let mut names: Vec<&str> = [index]
+            .into_iter()
+            .chain(self.ancestors(index))
+            .filter(|index| *index != Self::ROOT)
+            .map(|index| self.arena[index].value.name())
+            .collect();
+
+        names.reverse();
+        format!("/{}", names.join("/"))
+    }
+
+    // The path of every node, each built from its parent's
+    fn paths(&self) -> Vec<String> {
+        let mut paths: Vec<String> = Vec::with_capacity(self.arena.len());
+
         for node in &self.arena {
-            if node.value == value && (parent.is_some() && node.parent.is_some() && parent.unwrap() == node.parent.unwrap()) {
-                return Some(node.index);
+            paths.push(match node.parent {
+                None => "/".to_string(),
+                Some(Self::ROOT) => format!("/{}", node.value.name()),
+                Some(parent) => format!("{}/{}", paths[parent], node.value.name())
+            });
+        }
+
+        paths
+    }
+
+    fn size_of(&self, path: &str) -> Option<usize> {
+        let index = self.resolve(Self::ROOT, path)?;
+
+        let size = self.walk(index)
+            .map(|(index, _)| match self.arena[index].value {
+                FsEntry::File(_, size) => size,
+                FsEntry::Directory(_) => 0
+            })
+            .sum();
+
+        Some(size)
+    }
+
+    // The size of every node, files included, in a single pass
+    fn sizes(&self) -> Vec<usize> {
+        let mut sizes = vec![0; self.arena.len()];
+
+        for index in self.post_order() {
+            if let FsEntry::File(_, size) = self.arena[index].value {
+                sizes[index] = size;
+            }
+
+            if let Some(parent) = self.arena[index].parent {
+                sizes[parent] += sizes[index];
             }
         }
 
-        None
+        sizes
     }
 
-    fn depth(&self, idx: usize) -> usize {
-        match self.arena[idx].parent {
-            Some(id) => 1 + self.depth(id),
-            None => 0,
+    fn directories(&self) -> impl Iterator<Item = usize> + '_ {
+        self.walk(Self::ROOT).map(|(index, _)| index).filter(|index| self.arena[*index].value.is_dir())
+    }
+
+    // Picks directories, none inside another, whose deletion frees enough space for the update.
+    // Returns None when even deleting everything allowed isn't enough.
+    fn plan_cleanup(&self, cleanup: &Cleanup) -> Option<Plan> {
+        let sizes = self.sizes();
+        let needed = (sizes[Self::ROOT] + cleanup.disk.required).saturating_sub(cleanup.disk.total);
+
+        if needed == 0 {
+            return Some(Plan { directories: vec![], freed: 0, optimal: true });
+        }
+
+        let mut kept = vec![false; self.arena.len()];
+        for &index in &cleanup.protected {
+            for kept_index in [index].into_iter().chain(self.ancestors(index)) {
+                kept[kept_index] = true;
+            }
+        }
+
+        // Candidates in pre-order, so that skipping a directory's subtree is a jump forward
+        let order: Vec<(usize, usize)> = self.walk(Self::ROOT).collect();
+        let mut nodes_below = vec![1; self.arena.len()];
+        for index in self.post_order() {
+            if let Some(parent) = self.arena[index].parent {
+                nodes_below[parent] += nodes_below[index];
+            }
+        }
+
+        let candidates: Vec<(usize, usize)> = order
+            .iter()
+            .enumerate()
+            .filter(|(_, (index, _))| self.arena[*index].value.is_dir() && !kept[*index])
+            .map(|(position, (index, _))| (position, *index))
+            .collect();
+
+        let after: Vec<usize> = candidates
+            .iter()
+            .map(|(position, index)| candidates.partition_point(|(other, _)| *other < position + nodes_below[*index]))
+            .collect();
+
+        // Most bytes that can still be freed from each candidate on, and the largest single one left
+        let mut available = vec![0; candidates.len() + 1];
+        let mut largest = vec![0; candidates.len() + 1];
+        for position in (0..candidates.len()).rev() {
+            let size = sizes[candidates[position].1];
+            available[position] = available[position + 1].max(size + available[after[position]]);
+            largest[position] = largest[position + 1].max(size);
+        }
+
+        if available[0] < needed {
+            return None;
+        }
+
+        let cost = |count: usize, bytes: usize| match cleanup.objective {
+            Objective::FewestDeletions => (count, bytes),
+            Objective::LeastBytes => (bytes, count)
+        };
+
+        // Chosen directories are kept as linked lists sharing their beginnings
+        let mut chosen: Vec<(usize, Option<usize>)> = vec![];
+        let mut best: Option<((usize, usize), Option<usize>, usize)> = None;
+        let mut seen = HashSet::new();
+        let mut optimal = true;
+        let mut stack = vec![(0, 0, 0, None)];
+
+        while let Some((position, bytes, count, list)) = stack.pop() {
+            if bytes >= needed {
+                if best.is_none_or(|(best_cost, _, _)| cost(count, bytes) < best_cost) {
+                    best = Some((cost(count, bytes), list, bytes));
+                }
+
+                continue;
+            }
+
+            if position == candidates.len() || bytes + available[position] < needed {
+                continue;
+            }
+
+            // At least this many more deletions, and the plan frees at least what's needed
+            let missing = (needed - bytes).div_ceil(largest[position]);
+            if best.is_some_and(|(best_cost, _, _)| cost(count + missing, needed) >= best_cost) {
+                continue;
+            }
+
+            if !seen.insert((position, bytes, count)) {
+                continue;
+            }
+
+            if seen.len() > cleanup.budget {
+                optimal = false;
+                break;
+            }
+
+            // Deleting is tried first, which quickly finds a plan to prune against
+            let index = candidates[position].1;
+            chosen.push((index, list));
+
+            stack.push((position + 1, bytes, count, list));
+            stack.push((after[position], bytes + sizes[index], count + 1, Some(chosen.len() - 1)));
+        }
+
+        best.map(|(_, mut list, freed)| {
+            let mut directories = vec![];
+            while let Some(link) = list {
+                directories.push(chosen[link].0);
+                list = chosen[link].1;
+            }
+
+            directories.reverse();
+            Plan { directories, freed, optimal }
+        })
+    }
+
+    // A terminal session that lists every directory, which `build_tree` turns back into this tree
+    fn transcript(&self, traversal: Traversal) -> String {
+        let directories: Vec<usize> = match traversal {
+            Traversal::DepthFirst => self.directories().collect(),
+            Traversal::BreadthFirst => {
+                let mut order = vec![];
+                let mut queue = VecDeque::from([Self::ROOT]);
+
+                while let Some(index) = queue.pop_front() {
+                    order.push(index);
+                    queue.extend(self.arena[index].children.iter().filter(|child| self.arena[**child].value.is_dir()));
+                }
+
+                order
+            }
+        };
+
+        let depths = self.depths();
+        let mut lines = vec!["$ cd /".to_string()];
+        let mut current = Self::ROOT;
+
+        for index in directories {
+            self.navigate(current, index, &depths, &mut lines);
+            current = index;
+
+            lines.push("$ ls".to_string());
+            for child in &self.arena[index].children {
+                lines.push(match &self.arena[*child].value {
+                    FsEntry::Directory(name) => format!("dir {}", name),
+                    FsEntry::File(name, size) => format!("{} {}", size, name)
+                });
+            }
+        }
+
+        lines.join("\n") + "\n"
+    }
+
+    // One `cd` per directory on the way, up to where both paths meet and then down
+    fn navigate(&self, from: usize, to: usize, depths: &[usize], lines: &mut Vec<String>) {
+        let parent = |index: usize| self.arena[index].parent.expect("The root has no parent.");
+
+        let (mut current, mut target) = (from, to);
+        let (mut current_depth, mut target_depth) = (depths[from], depths[to]);
+        let mut down = vec![];
+
+        while current_depth > target_depth {
+            lines.push("$ cd ..".to_string());
+            current = parent(current);
+            current_depth -= 1;
+        }
+
+        while target_depth > current_depth {
+            down.push(target);
+            target = parent(target);
+            target_depth -= 1;
+        }
+
+        while current != target {
+            lines.push("$ cd ..".to_string());
+            current = parent(current);
+            down.push(target);
+            target = parent(target);
+        }
+
+        for index in down.iter().rev() {
+            lines.push(format!("$ cd {}", self.arena[*index].value.name()));
         }
     }
+
+    // Directories at most `max_depth` below the root as (size, path), largest first like `du | sort -rh`
+    fn disk_usage(&self, max_depth: Option<usize>) -> Vec<(usize, String)> {
+        let sizes = self.sizes();
+        let paths = self.paths();
+
+        let mut usage: Vec<(usize, String)> = self.walk(Self::ROOT)
+            .filter(|(index, depth)| self.arena[*index].value.is_dir() && max_depth.is_none_or(|max| *depth <= max))
+            .map(|(index, _)| (sizes[index], paths[index].clone()))
+            .collect();
+
+        usage.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        usage
+    }
+
+    // Draws the tree the way `tree --du -h` does
+    fn render(&self) -> String {
+        let sizes = self.sizes();
+        let mut lines = vec![format!("/ ({})", format_bytes(sizes[Self::ROOT]))];
+        let (mut directories, mut files) = (0, 0);
+
+        // (node, what goes before its branch, whether it's the last child)
+        let mut stack: Vec<(usize, String, bool)> = self.arena[Self::ROOT].children
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(position, child)| (*child, String::new(), position == 0))
+            .collect();
+
+        while let Some((index, prefix, last)) = stack.pop() {
+            let node = &self.arena[index];
+            let branch = if last { "└── " } else { "├── " };
+
+            lines.push(format!("{}{}{} ({})", prefix, branch, node.value.name(), format_bytes(sizes[index])));
+
+            if node.value.is_dir() { directories += 1 } else { files += 1 }
+
+            let prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+            for (position, child) in node.children.iter().rev().enumerate() {
+                stack.push((*child, prefix.clone(), position == 0));
+            }
+        }
+
+        lines.push(String::new());
+        lines.push(format!("{} directories, {} files", directories, files));
+        lines.join("\n")
+    }
+
+    // Every node as {"name", "type", "size"}, directories also list their "children"
+    fn to_json(&self) -> String {
+        enum Step {
+            Enter(usize, bool),
+            Leave
+        }
+
+        let sizes = self.sizes();
+        let mut json = String::new();
+        let mut stack = vec![Step::Enter(Self::ROOT, true)];
+
+        while let Some(step) = stack.pop() {
+            let (index, first) = match step {
+                Step::Enter(index, first) => (index, first),
+                Step::Leave => {
+                    json.push_str("]}");
+                    continue;
+                }
+            };
+
+            let node = &self.arena[index];
+            let kind = if node.value.is_dir() { "dir" } else { "file" };
+
+            if !first {
+                json.push(',');
+            }
+
+            json.push_str(&format!("{{\"name\":{},\"type\":\"{}\",\"size\":{}", json_string(node.value.name()), kind, sizes[index]));
+
+            if node.value.is_dir() {
+                json.push_str(",\"children\":[");
+                stack.push(Step::Leave);
+
+                for (position, child) in node.children.iter().enumerate().rev() {
+                    stack.push(Step::Enter(*child, position == 0));
+                }
+            } else {
+                json.push('}');
+            }
+        }
+
+        json
+    }
 }
 
-impl<T> Tree<T> where T: PartialEq + Display {
-    fn rec_print(&self, index: usize, fmt: &mut Formatter<'_>) -> Result {
-        if self.arena[index].children.is_empty() {
-            Ok(())
-        } else {
-            self.arena[index].children
-                .iter()
-                .fold(Ok(()), |result, index| {
-                    result
-                        .and(writeln!(fmt, "{}- {}", "  ".repeat(self.depth(*index)), self.arena[*index].value))
-                        .and(self.rec_print(*index, fmt))
-                })
+fn json_string(source: &str) -> String {
+    let mut escaped = String::from("\"");
+
+    for character in source.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            character if (character as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", character as u32)),
+            character => escaped.push(character)
         }
     }
+
+    escaped.push('"');
+    escaped
 }
 
 impl<T> Display for Tree<T> where T: PartialEq + Display {
-    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result {
-        self.rec_print(0, fmt)
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        if self.arena.is_empty() {
+            return Ok(());
+        }
+
+        self.walk(0)
+            .skip(1)
+            .try_for_each(|(index, depth)| writeln!(fmt, "{}- {}", "  ".repeat(depth), self.arena[index].value))
     }
 }
 
+// Reads a directory on disk, entries sorted by name. Symbolic links are left out.
+fn read_directory(path: &Path) -> io::Result<Tree<FsEntry>> {
+    let mut tree = Tree::new();
+    let root = tree.insert(FsEntry::Directory("/".to_string()), None);
+    let mut stack = vec![(path.to_path_buf(), root)];
+
+    while let Some((path, index)) = stack.pop() {
+        let mut entries = fs::read_dir(&path)?.collect::<io::Result<Vec<fs::DirEntry>>>()?;
+        entries.sort_by_key(|entry| entry.file_name());
+
+        for entry in entries {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let metadata = fs::symlink_metadata(entry.path())?;
+
+            if metadata.is_dir() {
+                let child = tree.insert(FsEntry::Directory(name), Some(index));
+                stack.push((entry.path(), child));
+            } else if metadata.is_file() {
+                tree.insert(FsEntry::File(name, metadata.len() as usize), Some(index));
+            }
+        }
+    }
+
+    Ok(tree)
+}
+
+// Reads a tree back from its `Display` output, such as "  - a (dir)" or "    - i (file, size=584)"
+fn parse_description(source: &str) -> Tree<FsEntry> {
+    let mut tree = Tree::new();
+    let mut parents = vec![tree.insert(FsEntry::Directory("/".to_string()), None)];
+
+    for line in source.lines().filter(|line| !line.trim().is_empty()) {
+        let entry = line.trim_start();
+        let depth = (line.len() - entry.len()) / 2;
+        assert!(depth >= 1 && depth <= parents.len(), "Invalid indentation: {}", line);
+
+        let (name, kind) = entry
+            .strip_prefix("- ")
+            .and_then(|entry| entry.strip_suffix(')'))
+            .and_then(|entry| entry.rsplit_once(" ("))
+            .expect("Expected '- name (dir)' or '- name (file, size=N)'.");
+
+        parents.truncate(depth);
+        let parent = parents[depth - 1];
+
+        if kind == "dir" {
+            parents.push(tree.insert(FsEntry::Directory(name.to_string()), Some(parent)));
+        } else {
+            let size = kind
+                .strip_prefix("file, size=")
+                .and_then(|size| size.parse::<usize>().ok())
+                .expect("Invalid file size.");
+
+            tree.insert(FsEntry::File(name.to_string(), size), Some(parent));
+        }
+    }
+
+    tree
+}
+
 #[derive(Debug)]
 struct Node<T> where T: PartialEq {
-    index: usize,
     value: T,
     parent: Option<usize>,
     children: Vec<usize>,
+    by_name: HashMap<String, usize>,
 }
 
 impl<T> Node<T> where T: PartialEq {
-    fn new(index: usize, value: T, parent: Option<usize>) -> Self {
+    fn new(value: T, parent: Option<usize>) -> Self {
         Self {
-            index,
             value,
             parent,
             children: vec![],
+            by_name: HashMap::new(),
         }
     }
 }
@@ -106,13 +667,18 @@ impl<T> Node<T> where T: PartialEq {
 fn main() {
     let input = include_str!("../input/day07.input");
 
-    // Part one
-    let size_sum = first_puzzle(input);
-    println!("Directories of size <= 100000 add up to {}.", size_sum);
-
-    // Part two
-    let dir_size = second_puzzle(input);
-    println!("Directory of size {} should be deleted.", dir_size);
+    Day::new("day07", &[
+        "Directories of size <= 100000 add up to {}.",
+        "Directory of size {} should be deleted.",
+    ])
+    .solution(Solution::new("raquel").part(first_puzzle).part(second_puzzle))
+    .report("size", print_sizes)
+    .report("du", print_disk_usage)
+    .report("cleanup", print_cleanup)
+    .report("transcript", print_transcript)
+    .report("tree", |source, _| { println!("{}", build_tree(source).render()); Ok(()) })
+    .report("json", |source, _| { println!("{}", build_tree(source).to_json()); Ok(()) })
+    .run(input);
 }
 
 #[test]
@@ -126,44 +692,187 @@ fn sample() {
     assert_eq!(second_puzzle(sample), 24933642);
 }
 
+#[test]
+fn filesystem() {
+    let tree = build_tree(include_str!("../sample/day07.input"));
+
+    assert_eq!(tree.size_of("/a/e"), Some(584));
+    assert_eq!(tree.size_of("/a/./e/../e"), Some(584));
+    assert_eq!(tree.size_of("/d/k"), Some(7214296));
+    assert_eq!(tree.size_of("/"), Some(48381165));
+    assert_eq!(tree.size_of("/a/x"), None);
+    assert_eq!(tree.size_of("/b.txt/x"), None);
+
+    let e = tree.resolve(Tree::ROOT, "a/e").unwrap();
+    assert_eq!(tree.path(e), "/a/e");
+    assert_eq!(tree.resolve(e, "../../d"), tree.resolve(Tree::ROOT, "/d"));
+    assert_eq!(tree.ancestors(e).map(|index| tree.path(index)).collect::<Vec<String>>(), vec!["/a", "/"]);
+
+    let walked: Vec<String> = tree.walk(Tree::ROOT).map(|(index, depth)| format!("{}{}", depth, tree.path(index))).collect();
+    assert_eq!(walked[..5], ["0/", "1/a", "2/a/e", "3/a/e/i", "2/a/f"]);
+    assert_eq!(tree.directories().count(), 4);
+
+    // Going into directories before listing them, listing twice and moving through several at once
+    let tree = build_tree("$ cd /x/y\n$ ls\n10 f\n$ ls\n10 f\ndir z\n$ cd z/../../y/z\n$ ls\n5 g\n$ cd /\n$ ls\ndir x\n1 h\n");
+    assert_eq!(tree.size_of("/x/y"), Some(15));
+    assert_eq!(tree.size_of("/"), Some(16));
+    assert_eq!(tree.arena.len(), 7);
+}
+
+#[test]
+fn deep_tree() {
+    // One file per directory, every directory nested in the previous one
+    let depth = 50000;
+    let transcript = format!("$ cd /\n{}", "$ ls\n1 f\ndir d\n$ cd d\n".repeat(depth));
+
+    let tree = build_tree(&transcript);
+    let sizes = tree.sizes();
+
+    assert_eq!(sizes[Tree::ROOT], depth);
+    assert_eq!(tree.directories().count(), depth + 1);
+    assert_eq!(first_puzzle(&transcript), depth + depth * (depth - 1) / 2);
+}
+
+#[test]
+fn reports() {
+    let tree = build_tree(include_str!("../sample/day07.input"));
+
+    let usage = tree.disk_usage(Some(1));
+    assert_eq!(usage, vec![(48381165, "/".to_string()), (24933642, "/d".to_string()), (94853, "/a".to_string())]);
+    assert_eq!(tree.disk_usage(None).len(), 4);
+    assert!(tree.paths().iter().enumerate().all(|(index, path)| *path == tree.path(index)));
+
+    let rendered = tree.render();
+    let lines: Vec<&str> = rendered.lines().collect();
+    assert_eq!(lines[0], "/ (46.1 MiB)");
+    assert_eq!(lines[1], "├── a (92.6 KiB)");
+    assert_eq!(lines[3], "│   │   └── i (584 B)");
+    assert_eq!(lines[9], "└── d (23.8 MiB)");
+    assert_eq!(lines[13], "    └── k (6.9 MiB)");
+    assert_eq!(lines[15], "3 directories, 10 files");
+
+    let tree = build_tree("$ cd /\n$ ls\ndir a b\n3 \"x\".txt\n$ cd a b\n$ ls\n");
+    assert_eq!(tree.to_json(), concat!(
+        r#"{"name":"/","type":"dir","size":3,"children":["#,
+        r#"{"name":"a b","type":"dir","size":0,"children":[]},"#,
+        r#"{"name":"\"x\".txt","type":"file","size":3}]}"#
+    ));
+}
+
+#[test]
+fn cleanup() {
+    let tree = build_tree(include_str!("../sample/day07.input"));
+    let paths = |plan: &Plan| -> Vec<String> { plan.directories.iter().map(|index| tree.path(*index)).collect() };
+
+    let plan = tree.plan_cleanup(&Cleanup::new(Disk::DEVICE, Objective::FewestDeletions)).unwrap();
+    assert_eq!((paths(&plan), plan.freed, plan.optimal), (vec!["/d".to_string()], 24933642, true));
+
+    // Needs 8381165 bytes: /a and /d/... can't be deleted separately, so /d is still the cheapest
+    let plan = tree.plan_cleanup(&Cleanup::new(Disk::DEVICE, Objective::LeastBytes)).unwrap();
+    assert_eq!(paths(&plan), vec!["/d"]);
+
+    // Only the two small directories can go, which frees far too little
+    let mut protected = Cleanup::new(Disk::DEVICE, Objective::LeastBytes);
+    protected.protected = vec![tree.resolve(Tree::ROOT, "/d/k").unwrap()];
+    assert_eq!(tree.plan_cleanup(&protected), None);
+
+    protected.disk = Disk { total: 48381165, required: 90000 };
+    let plan = tree.plan_cleanup(&protected).unwrap();
+    assert_eq!((paths(&plan), plan.freed), (vec!["/a".to_string()], 94853));
+
+    protected.disk = Disk { total: 48381165, required: 500 };
+    assert_eq!(paths(&tree.plan_cleanup(&protected).unwrap()), vec!["/a/e"]);
+
+    protected.disk = Disk { total: 100000000, required: 1 };
+    assert_eq!(tree.plan_cleanup(&protected).unwrap().directories, vec![]);
+
+    // Two directories come closer to what's needed than any single one
+    let tree = build_tree("$ cd /\n$ ls\ndir a\ndir b\ndir c\n$ cd a\n$ ls\n60 x\n$ cd ../b\n$ ls\n45 y\n$ cd ../c\n$ ls\n50 z\n");
+    let paths = |plan: &Plan| -> Vec<String> { plan.directories.iter().map(|index| tree.path(*index)).collect() };
+    let disk = Disk { total: 155, required: 95 };
+
+    let plan = tree.plan_cleanup(&Cleanup::new(disk, Objective::LeastBytes)).unwrap();
+    assert_eq!((paths(&plan), plan.freed), (vec!["/b".to_string(), "/c".to_string()], 95));
+
+    let plan = tree.plan_cleanup(&Cleanup::new(disk, Objective::FewestDeletions)).unwrap();
+    assert_eq!((paths(&plan), plan.freed), (vec!["/".to_string()], 155));
+
+    // The puzzle only ever deletes a single directory, here the innermost one is enough
+    let nested = "$ cd /\n$ ls\ndir a\n$ cd a\n$ ls\ndir b\n30000000 f\n$ cd b\n$ ls\n20000000 g\n";
+    assert_eq!(second_puzzle(nested), 20000000);
+    assert_eq!(second_puzzle(nested), build_tree(nested).plan_cleanup(&Cleanup::new(Disk::DEVICE, Objective::FewestDeletions)).unwrap().freed);
+}
+
+#[test]
+fn transcripts() {
+    let sample = include_str!("../sample/day07.input");
+    let tree = build_tree(sample);
+
+    assert_eq!(tree.transcript(Traversal::DepthFirst).trim_end(), sample.trim_end());
+    let depths = tree.depths();
+    assert!(tree.walk(Tree::ROOT).all(|(index, depth)| depths[index] == depth));
+
+    let breadth_first = tree.transcript(Traversal::BreadthFirst);
+    assert!(breadth_first.contains("$ cd d\n$ ls\n4060174 j\n8033020 d.log\n5626152 d.ext\n7214296 k\n$ cd ..\n$ cd a\n$ cd e\n"));
+    assert_eq!(build_tree(&breadth_first).to_string(), tree.to_string());
+
+    let described = parse_description(&tree.to_string());
+    assert_eq!(described.to_string(), tree.to_string());
+    assert_eq!(described.disk_usage(None), tree.disk_usage(None));
+
+    // A real directory, with an empty directory and a name with spaces
+    let root = std::env::temp_dir().join(format!("day07-transcripts-{}", std::process::id()));
+    fs::create_dir_all(root.join("a/b c")).unwrap();
+    fs::create_dir_all(root.join("empty")).unwrap();
+    fs::write(root.join("top.txt"), vec![0; 100]).unwrap();
+    fs::write(root.join("a/x"), vec![0; 20]).unwrap();
+    fs::write(root.join("a/b c/y z"), vec![0; 3]).unwrap();
+
+    let read = read_directory(&root);
+    fs::remove_dir_all(&root).unwrap();
+    let read = read.unwrap();
+
+    for traversal in [Traversal::DepthFirst, Traversal::BreadthFirst] {
+        let tree = build_tree(&read.transcript(traversal));
+
+        assert_eq!(tree.size_of("/"), Some(123));
+        assert_eq!(tree.size_of("/a"), Some(23));
+        assert_eq!(tree.size_of("/a/b c/y z"), Some(3));
+        assert_eq!(tree.size_of("/empty"), Some(0));
+        assert_eq!(tree.to_string(), read.to_string());
+    }
+}
+
 fn first_puzzle(source: &str) -> usize {
     let tree = build_tree(source);
 
-    tree.arena
-        .iter()
-        .enumerate()
-        .filter(|(_, node)| matches!(node.value, FsEntry::Directory(_)))
-        .map(|(index, _)| calc_size(&tree, index))
+    let sizes = tree.sizes();
+
+    tree.directories()
+        .map(|index| sizes[index])
         .filter(|dir_size| *dir_size <= 100000)
         .sum()
 }
 
+// The smallest single directory that frees enough space, no need for the planner's search
 fn second_puzzle(source: &str) -> usize {
-    let total_size_available = 70000000;
-    let unused_size_target = 30000000;
-
     let tree = build_tree(source);
-    let used_size = calc_size(&tree, 0);
 
-    let mut candidates: Vec<usize> = tree.arena
-        .iter()
-        .enumerate()
-        .filter(|(_, node)| matches!(node.value, FsEntry::Directory(_)))
-        .map(|(index, _)| calc_size(&tree, index))
-        .filter(|dir_size| (total_size_available - used_size) + dir_size >= unused_size_target)
-        .collect();
+    let sizes = tree.sizes();
+    let needed = (sizes[Tree::ROOT] + Disk::DEVICE.required).saturating_sub(Disk::DEVICE.total);
 
-    candidates.sort();
-    *candidates.first().unwrap()
+    tree.directories()
+        .map(|index| sizes[index])
+        .filter(|dir_size| *dir_size >= needed)
+        .min()
+        .expect("Not even deleting everything frees enough space.")
 }
 
 fn build_tree(source: &str) -> Tree<FsEntry> {
     let mut tree = Tree::new();
-    let mut index = 0;
+    let mut index = tree.insert(FsEntry::Directory("/".to_string()), None);
     let mut ls_called = false;
 
-    let root = tree.insert(FsEntry::Directory("/".to_string()), None);
-
     source
         .lines()
         .for_each(|line| {
@@ -172,15 +881,10 @@ fn build_tree(source: &str) -> Tree<FsEntry> {
 
                 ls_called = false;
                 match command {
-                    Command::CdBack => index = tree.arena[index].parent.expect("Directory doesn't have a parent."),
-                    Command::CdRoot => index = root,
                     Command::Ls => ls_called = true,
-                    Command::CdDir(name) => {
-                        let dir_index = tree
-                            .find(FsEntry::Directory(name), Some(index))
-                            .expect("Directory does not contain this directory");
-            
-                        index = dir_index;
+                    Command::Cd(path) => {
+                        assert!(tree.resolve(index, &path).is_none_or(|dir| tree.arena[dir].value.is_dir()), "Not a directory: {}", path);
+                        index = tree.make_dirs(index, &path);
                     }
                 }
             } else {
@@ -188,44 +892,130 @@ fn build_tree(source: &str) -> Tree<FsEntry> {
                     panic!("Tried to parse 'ls' results before an 'ls' call.");
                 }
 
-                let result = parse_result(line);
-                tree.insert(result, Some(index));
+                tree.add(index, parse_result(line));
             }
         });
 
     tree
 }
 
-fn calc_size(tree: &Tree<FsEntry>, index: usize) -> usize {
-    let node = &tree.arena[index];
+// `--report size <path...>`, paths are looked up from the root
+fn print_sizes(source: &str, args: &[String]) -> Result<(), String> {
+    let tree = build_tree(source);
 
-    match node.value {
-        FsEntry::File(_, size) => size,
-        FsEntry::Directory(_) => {
-            node
-                .children
-                .iter()
-                .map(|index| calc_size(tree, *index))
-                .sum()
+    for path in args {
+        match tree.resolve(Tree::ROOT, path) {
+            Some(index) => println!("{:>12}  {}", tree.size_of(&tree.path(index)).unwrap(), tree.path(index)),
+            None => println!("{:>12}  {}", "not found", path)
         }
     }
+
+    Ok(())
+}
+
+// `--report du [max depth]`
+fn print_disk_usage(source: &str, args: &[String]) -> Result<(), String> {
+    let max_depth = args
+        .first()
+        .map(|depth| depth.parse::<usize>().map_err(|_| format!("Invalid depth '{}'.", depth)))
+        .transpose()?;
+
+    for (size, path) in build_tree(source).disk_usage(max_depth) {
+        println!("{:>10}  {}", format_bytes(size), path);
+    }
+
+    Ok(())
+}
+
+// `--report cleanup [--total bytes] [--required bytes] [--objective fewest|bytes] [--protect path]... [--budget states]`
+fn print_cleanup(source: &str, args: &[String]) -> Result<(), String> {
+    let tree = build_tree(source);
+    let mut cleanup = Cleanup::new(Disk::DEVICE, Objective::FewestDeletions);
+    let mut args = args.iter();
+
+    let number = |option: &str, value: Option<&String>| value.and_then(|value| value.parse::<usize>().ok()).ok_or_else(|| format!("Expected a number after '{}'.", option));
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--total" => cleanup.disk.total = number(arg, args.next())?,
+            "--required" => cleanup.disk.required = number(arg, args.next())?,
+            "--budget" => cleanup.budget = number(arg, args.next())?,
+            "--objective" => cleanup.objective = match args.next().map(|value| value.as_str()) {
+                Some("fewest") => Objective::FewestDeletions,
+                Some("bytes") => Objective::LeastBytes,
+                _ => return Err("The objective must be 'fewest' or 'bytes'.".to_string())
+            },
+            "--protect" => {
+                let path = args.next().ok_or("Expected a path to protect.")?;
+                cleanup.protected.push(tree.resolve(Tree::ROOT, path).ok_or_else(|| format!("No such path: {}", path))?);
+            }
+            other => return Err(format!("Unknown option '{}'.", other))
+        }
+    }
+
+    let sizes = tree.sizes();
+    let used = sizes[Tree::ROOT];
+    let needed = (used + cleanup.disk.required).saturating_sub(cleanup.disk.total);
+    println!("{} used of {}, {} more needed", format_bytes(used), format_bytes(cleanup.disk.total), format_bytes(needed));
+
+    match tree.plan_cleanup(&cleanup) {
+        Some(plan) => {
+            for index in &plan.directories {
+                println!("{:>10}  {}", format_bytes(sizes[*index]), tree.path(*index));
+            }
+
+            println!("Deleting {} directories frees {} bytes.", plan.directories.len(), plan.freed);
+            if !plan.optimal {
+                println!("The search ran out of budget, a better plan may exist.");
+            }
+        }
+        None => println!("Not even deleting everything that isn't protected frees enough space.")
+    }
+
+    Ok(())
+}
+
+// Prints a session exploring the puzzle's tree, a directory on disk or a `Display`ed tree,
+// `--report transcript [--order depth|breadth] [--dir path | --described file]`
+fn print_transcript(source: &str, args: &[String]) -> Result<(), String> {
+    let mut traversal = Traversal::DepthFirst;
+    let mut tree = None;
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--order" => traversal = match args.next().map(|value| value.as_str()) {
+                Some("depth") => Traversal::DepthFirst,
+                Some("breadth") => Traversal::BreadthFirst,
+                _ => return Err("The order must be 'depth' or 'breadth'.".to_string())
+            },
+            "--dir" => {
+                let path = args.next().ok_or("Expected a directory.")?;
+                tree = Some(read_directory(Path::new(path)).map_err(|error| format!("Could not read {}: {}", path, error))?);
+            }
+            "--described" => {
+                let path = args.next().ok_or("Expected a file describing the tree.")?;
+                tree = Some(parse_description(&fs::read_to_string(path).map_err(|error| format!("Could not read {}: {}", path, error))?));
+            }
+            other => return Err(format!("Unknown option '{}'.", other))
+        }
+    }
+
+    let tree = tree.unwrap_or_else(|| build_tree(source));
+    print!("{}", tree.transcript(traversal));
+    Ok(())
 }
 
 fn parse_command(source: &str) -> Command {
     if source.starts_with("$ ") {
-        let statement = &mut source.strip_prefix("$ ").unwrap().split(' ');
+        let statement = &mut source.strip_prefix("$ ").unwrap().splitn(2, ' ');
         let command = statement.next().expect("Could not find a valid command (must be 'cd' or 'ls').");
 
         match command {
             "ls" => return Command::Ls,
             "cd" => {
                 let argument = statement.next().expect("Could not find a valid 'cd' argument.");
-                
-                match argument {
-                    "/" => return Command::CdRoot,
-                    ".." => return Command::CdBack,
-                    _ => return Command::CdDir(argument.to_string())
-                }
+                return Command::Cd(argument.to_string());
             }
             _ => panic!("Could not find a valid command (must be 'cd' or 'ls').")
         }
@@ -239,10 +1029,10 @@ fn parse_result(source: &str) -> FsEntry {
         return FsEntry::Directory(source.strip_prefix("dir ").expect("Invalid format for directory result.").to_string());
     }
 
-    let mut size_name = source.split(' ');
+    let (size, name) = source.split_once(' ').expect("Could not find file name.");
 
-    let size = size_name.next().expect("Could not find file size.").parse::<usize>().expect("Invalid file size.");
-    let name = size_name.next().expect("Could not find file name.").to_string();
+    let size = size.parse::<usize>().expect("Invalid file size.");
+    let name = name.to_string();
 
     FsEntry::File(name, size)
 }
\ No newline at end of file
@@ -1,168 +1,344 @@
-fn main() {
-    let input = include_str!("../input/day08.input");
+use std::fs;
+use adventofcode_2022::runner::{Day, Solution};
+
+// Where the trees are seen from, also where an elf sitting on a tree is looking
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Direction {
+    Up,
+    Down,
+    Left,
+    Right
+}
 
-    // Part one
-    let visible_count = first_puzzle(input);
-    println!("A total of {} trees are visible from outside the grid.", visible_count);
+const DIRECTIONS: [Direction; 4] = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
 
-    // Part two
-    let highest_score = second_puzzle(input);
-    println!("The highest scenic score is {}.", highest_score);
+// Viewing distances and visibility of a single tree, indexed like `DIRECTIONS`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct View {
+    distances: [usize; 4],
+    visible: [bool; 4]
 }
 
-#[test]
-fn sample() {
-    let sample = include_str!("../sample/day08.input");
+impl View {
+    fn is_visible(&self) -> bool {
+        self.visible.iter().any(|visible| *visible)
+    }
 
-    assert_eq!(first_puzzle(sample), 21);
-    assert_eq!(second_puzzle(sample), 8);
+    fn scenic_score(&self) -> usize {
+        self.distances.iter().product()
+    }
+
+    // The directions the tree can be seen from outside the grid
+    fn seen_from(&self) -> Vec<Direction> {
+        DIRECTIONS
+            .into_iter()
+            .filter(|direction| self.visible[*direction as usize])
+            .collect()
+    }
 }
 
-fn first_puzzle(source: &str) -> usize{
-    let grid = parse_grid(source);
+// What a heat map shows for every tree
+#[derive(Clone, Copy)]
+enum Layer {
+    Visibility,
+    Scenic
+}
 
-    let width = grid[0].len();
-    let height = grid.len();
+struct Forest {
+    width: usize,
+    height: usize,
+    heights: Vec<u32>,
+    views: Vec<View>
+}
 
-    let mut count = 0;
+impl Forest {
+    fn new(grid: Vec<Vec<u32>>) -> Self {
+        let height = grid.len();
+        let width = grid.first().map_or(0, Vec::len);
+        assert!(grid.iter().all(|row| row.len() == width), "All rows must be as wide.");
+
+        let mut forest = Forest {
+            width,
+            height,
+            heights: grid.into_iter().flatten().collect(),
+            views: vec![View::default(); width * height]
+        };
+
+        for direction in DIRECTIONS {
+            for line in forest.lines(direction) {
+                forest.sweep(direction, &line);
+            }
+        }
 
-    for i in 1..grid[0].len() - 1 {
-        for j in 1..grid.len() - 1 {
-            let tree = grid[j][i];
+        forest
+    }
 
-            let mut left_visible = true;
-            let mut top_visible = true;
-            let mut right_visible = true;
-            let mut bot_visible = true;
+    fn view(&self, x: usize, y: usize) -> Option<&View> {
+        if x < self.width && y < self.height { Some(&self.views[y * self.width + x]) } else { None }
+    }
 
-            // Left
-            for k in 0..i {
-                let adjacent = grid[j][k];
-                if adjacent >= tree {
-                    left_visible = false;
-                    break;
-                }
-            }
+    fn visibility_grid(&self) -> Vec<Vec<bool>> {
+        self.views.chunks(self.width.max(1)).map(|row| row.iter().map(View::is_visible).collect()).collect()
+    }
 
-            // Top
-            for k in 0..j {
-                let adjacent = grid[k][i];
-                if adjacent >= tree {
-                    top_visible = false;
-                    break;
-                }
+    fn scenic_grid(&self) -> Vec<Vec<usize>> {
+        self.views.chunks(self.width.max(1)).map(|row| row.iter().map(View::scenic_score).collect()).collect()
+    }
+
+    // How strongly every tree shows up on a heat map, from 0 to 1. Scenic scores spread over
+    // several orders of magnitude, so they are scaled logarithmically.
+    fn levels(&self, layer: Layer) -> Vec<Vec<f64>> {
+        match layer {
+            Layer::Visibility => self.visibility_grid()
+                .iter()
+                .map(|row| row.iter().map(|visible| if *visible { 1.0 } else { 0.0 }).collect())
+                .collect(),
+            Layer::Scenic => {
+                let scores = self.scenic_grid();
+                let max = scores.iter().flatten().max().copied().unwrap_or(0);
+                let scale = ((max + 1) as f64).ln();
+
+                scores
+                    .iter()
+                    .map(|row| row.iter().map(|score| if max == 0 { 0.0 } else { ((score + 1) as f64).ln() / scale }).collect())
+                    .collect()
             }
+        }
+    }
 
-            // Right
-            for k in i+1..width {
-                let adjacent = grid[j][k];
-                if adjacent >= tree {
-                    right_visible = false;
-                    break;
-                }
+    fn render_ascii(&self, layer: Layer) -> String {
+        let ramp: Vec<char> = " .:-=+*#%@".chars().collect();
+
+        self.levels(layer)
+            .iter()
+            .map(|row| row.iter().map(|level| ramp[(level * (ramp.len() - 1) as f64).round() as usize]).collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    // Every tree's height on a background coloured by its level
+    fn render_ansi(&self, layer: Layer) -> String {
+        self.levels(layer)
+            .iter()
+            .enumerate()
+            .map(|(y, row)| {
+                let cells: String = row
+                    .iter()
+                    .enumerate()
+                    .map(|(x, level)| {
+                        let [r, g, b] = heat(*level);
+                        format!("\x1b[48;2;{};{};{}m{}", r, g, b, self.heights[y * self.width + x])
+                    })
+                    .collect();
+
+                format!("{}\x1b[0m", cells)
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    // Plain PGM, one grey level per tree
+    fn to_pgm(&self, layer: Layer) -> String {
+        let rows: Vec<String> = self.levels(layer)
+            .iter()
+            .map(|row| row.iter().map(|level| ((level * 255.0).round() as u8).to_string()).collect::<Vec<String>>().join(" "))
+            .collect();
+
+        format!("P2\n{} {}\n255\n{}\n", self.width, self.height, rows.join("\n"))
+    }
+
+    // Plain PPM, coloured like the ANSI rendering
+    fn to_ppm(&self, layer: Layer) -> String {
+        let rows: Vec<String> = self.levels(layer)
+            .iter()
+            .map(|row| row.iter().map(|level| heat(*level).map(|channel| channel.to_string()).join(" ")).collect::<Vec<String>>().join("  "))
+            .collect();
+
+        format!("P3\n{} {}\n255\n{}\n", self.width, self.height, rows.join("\n"))
+    }
+
+    // Every row or column as cell indices, starting from the edge the trees are looking towards
+    fn lines(&self, direction: Direction) -> Vec<Vec<usize>> {
+        let (width, height) = (self.width, self.height);
+
+        match direction {
+            Direction::Up => (0..width).map(|x| (0..height).map(|y| y * width + x).collect()).collect(),
+            Direction::Down => (0..width).map(|x| (0..height).rev().map(|y| y * width + x).collect()).collect(),
+            Direction::Left => (0..height).map(|y| (0..width).map(|x| y * width + x).collect()).collect(),
+            Direction::Right => (0..height).map(|y| (0..width).rev().map(|x| y * width + x).collect()).collect()
+        }
+    }
+
+    // Keeps the trees that still block the view as a stack of decreasing heights, so that every
+    // tree is pushed and popped once per direction
+    fn sweep(&mut self, direction: Direction, line: &[usize]) {
+        let mut blocking: Vec<usize> = vec![];
+
+        for (position, &cell) in line.iter().enumerate() {
+            let height = self.heights[cell];
+
+            while blocking.last().is_some_and(|last| self.heights[line[*last]] < height) {
+                blocking.pop();
             }
 
-            // Bottom
-            for k in j+1..height {
-                let adjacent = grid[k][i];
-                if adjacent >= tree {
-                    bot_visible = false;
-                    break;
+            let view = &mut self.views[cell];
+            match blocking.last() {
+                Some(last) => view.distances[direction as usize] = position - last,
+                None => {
+                    view.distances[direction as usize] = position;
+                    view.visible[direction as usize] = true;
                 }
             }
 
-            if left_visible || top_visible || right_visible || bot_visible {
-                count += 1;
-            }
+            blocking.push(position);
         }
     }
-    
-    count += width * 2; // Top and bottom edges are visible
-    count += (height - 2) * 2; // Left and right edges are visible
-    count
 }
 
-fn second_puzzle(source: &str) -> usize{
-    let grid = parse_grid(source);
+// Goes from blue through green to red
+fn heat(level: f64) -> [u8; 3] {
+    let level = level.clamp(0.0, 1.0);
 
-    let width = grid[0].len();
-    let height = grid.len();
+    if level < 0.5 {
+        let t = level * 2.0;
+        [0, (t * 255.0).round() as u8, ((1.0 - t) * 255.0).round() as u8]
+    } else {
+        let t = (level - 0.5) * 2.0;
+        [(t * 255.0).round() as u8, ((1.0 - t) * 255.0).round() as u8, 0]
+    }
+}
 
-    let mut max_score = 0;
+fn main() {
+    let input = include_str!("../input/day08.input");
 
-    for i in 1..grid[0].len() - 1 {
-        for j in 1..grid.len() - 1 {
-            let tree = grid[j][i];
+    Day::new("day08", &[
+        "A total of {} trees are visible from outside the grid.",
+        "The highest scenic score is {}.",
+    ])
+    .solution(Solution::new("raquel").part(first_puzzle).part(second_puzzle))
+    .report("heatmap", print_heatmap)
+    .report("tree", print_tree)
+    .run(input);
+}
 
-            let mut left_visible = 0;
-            let mut top_visible = 0;
-            let mut right_visible = 0;
-            let mut bot_visible = 0;
+#[test]
+fn sample() {
+    let sample = include_str!("../sample/day08.input");
 
-            // Left
-            for k in (0..i).rev() {
-                let adjacent = grid[j][k];
+    assert_eq!(first_puzzle(sample), 21);
+    assert_eq!(second_puzzle(sample), 8);
+}
 
-                left_visible += 1;
-                if adjacent >= tree {
-                    break;
-                }
-            }
+#[test]
+fn shapes() {
+    // Single rows and columns are all edge
+    assert_eq!(first_puzzle("30373"), 5);
+    assert_eq!(second_puzzle("30373"), 0);
+    assert_eq!(first_puzzle("3\n0\n3\n7\n3"), 5);
+    assert_eq!(first_puzzle("5"), 1);
+    assert_eq!(first_puzzle(""), 0);
+    assert_eq!(second_puzzle(""), 0);
+
+    // The 1 and the 2 are hidden, the 5 sees 1 tree up and to each side but 2 down
+    assert_eq!(first_puzzle("33333\n31523\n33333"), 13);
+    assert_eq!(second_puzzle("303\n353\n303\n303"), 2);
+
+    // The puzzle's own example, the 5 in the middle of the second row
+    let forest = Forest::new(parse_grid(include_str!("../sample/day08.input")));
+    let view = forest.views[forest.width + 2];
+    assert_eq!(view.distances, [1, 2, 1, 2]);
+    assert_eq!(view.visible, [true, false, false, true]);
+}
 
-            // Top
-            for k in (0..j).rev() {
-                let adjacent = grid[k][i];
-                top_visible += 1;
+#[test]
+fn heatmaps() {
+    let forest = Forest::new(parse_grid(include_str!("../sample/day08.input")));
+
+    assert_eq!(forest.scenic_grid()[3][2], 8);
+    assert_eq!(forest.visibility_grid()[2], vec![true, true, false, true, true]);
+    assert_eq!(forest.render_ascii(Layer::Visibility).lines().nth(2), Some("@@ @@"));
+    assert_eq!(forest.render_ascii(Layer::Scenic).lines().next(), Some("     "));
+
+    let pgm = forest.to_pgm(Layer::Scenic);
+    let lines: Vec<&str> = pgm.lines().collect();
+    assert_eq!(lines[..3], ["P2", "5 5", "255"]);
+    assert_eq!(lines[6], "0 80 255 161 0");
+
+    let ppm = forest.to_ppm(Layer::Visibility);
+    assert!(ppm.starts_with("P3\n5 5\n255\n255 0 0  255 0 0"));
+    assert_eq!(heat(0.0), [0, 0, 255]);
+    assert_eq!(heat(0.5), [0, 255, 0]);
+
+    let view = forest.view(2, 3).unwrap();
+    assert_eq!(view.distances, [2, 1, 2, 2]);
+    assert_eq!(view.seen_from(), vec![Direction::Down, Direction::Left]);
+    assert!(forest.view(5, 0).is_none());
+}
 
-                if adjacent >= tree {
-                    break;
-                }
-            }
+fn first_puzzle(source: &str) -> usize {
+    Forest::new(parse_grid(source))
+        .views
+        .iter()
+        .filter(|view| view.is_visible())
+        .count()
+}
 
-            // Right
-            for k in i+1..width {
-                let adjacent = grid[j][k];
+fn second_puzzle(source: &str) -> usize {
+    Forest::new(parse_grid(source))
+        .views
+        .iter()
+        .map(View::scenic_score)
+        .max()
+        .unwrap_or(0)
+}
 
-                right_visible += 1;
-                if adjacent >= tree {
-                    break;
-                }
-            }
+// `--report heatmap [visibility|scenic] [ascii|ansi|pgm <file>|ppm <file>]`
+fn print_heatmap(source: &str, args: &[String]) -> Result<(), String> {
+    let forest = Forest::new(parse_grid(source));
+
+    let layer = match args.first().map(|arg| arg.as_str()) {
+        Some("visibility") => Layer::Visibility,
+        Some("scenic") | None => Layer::Scenic,
+        Some(other) => return Err(format!("Unknown layer '{}'.", other))
+    };
+
+    let write = |image: String| -> Result<(), String> {
+        let path = args.get(2).ok_or("Expected a file to write the image to.")?;
+        fs::write(path, image).map_err(|error| format!("Could not write {}: {}", path, error))?;
+        println!("Wrote a {}x{} image to {}.", forest.width, forest.height, path);
+        Ok(())
+    };
+
+    match args.get(1).map(|arg| arg.as_str()) {
+        Some("ascii") | None => println!("{}", forest.render_ascii(layer)),
+        Some("ansi") => println!("{}", forest.render_ansi(layer)),
+        Some("pgm") => write(forest.to_pgm(layer))?,
+        Some("ppm") => write(forest.to_ppm(layer))?,
+        Some(other) => return Err(format!("Unknown format '{}'.", other))
+    }
 
-            // Bottom
-            for k in j+1..height {
-                let adjacent = grid[k][i];
+    Ok(())
+}
 
-                bot_visible += 1;
-                if adjacent >= tree {
-                    break;
-                }
-            }
+// `--report tree <x> <y>`, counted from the top left corner
+fn print_tree(source: &str, args: &[String]) -> Result<(), String> {
+    let forest = Forest::new(parse_grid(source));
+    let coordinate = |index: usize| args.get(index).and_then(|arg| arg.parse::<usize>().ok()).ok_or("Expected the tree's x and y.");
+    let (x, y) = (coordinate(0)?, coordinate(1)?);
 
-            let scenic_score = left_visible * top_visible * right_visible * bot_visible;
+    let view = forest.view(x, y).ok_or_else(|| format!("There is no tree at ({}, {}).", x, y))?;
 
-            if scenic_score > max_score {
-                max_score = scenic_score
-            }
-        }
+    println!("Tree at ({}, {}) is {} tall.", x, y, forest.heights[y * forest.width + x]);
+    for direction in DIRECTIONS {
+        println!("  {:<5} sees {} trees{}", format!("{:?}", direction), view.distances[direction as usize], if view.visible[direction as usize] { ", visible from outside" } else { "" });
     }
-    
-    max_score
+    println!("Scenic score {}, seen from {:?}", view.scenic_score(), view.seen_from());
+    Ok(())
 }
 
 fn parse_grid(source: &str) -> Vec<Vec<u32>> {
-    let mut grid = vec![vec![]; 0];
-
     source
         .lines()
-        .enumerate()
-        .for_each(|(index, line)| {
-            grid.push(vec![]);
-
-            for c in line.chars() {
-                grid[index].push(c.to_digit(10).unwrap())
-            }
-        });
-
-    grid
-}
\ No newline at end of file
+        .map(|line| line.chars().map(|c| c.to_digit(10).expect("Invalid height.")).collect())
+        .collect()
+}
@@ -1,6 +1,7 @@
-use std::vec;
+use std::{collections::{HashMap, HashSet}, vec};
+use adventofcode_2022::runner::{Day, Solution};
 
-#[derive(Clone, Copy, Default, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
 struct Knot {
     x: i32,
     y: i32
@@ -14,49 +15,122 @@ impl Knot {
         }
     }
 
-    fn drag(&self, other: &mut Knot) {
+    // Moves `other` one step closer when it's further than the slack allows, returns whether it moved
+    fn drag(&self, other: &mut Knot, rope: &Rope) -> bool {
         let diff = self.diff(other);
 
-        if diff.x.abs() > 1 || diff.y.abs() > 1 {
-            if diff.x.abs() == diff.y.abs() {
-                other.x += if diff.x > 0 { 1 } else { -1 };
-                other.y += if diff.y > 0 { 1 } else { -1 };
-                return;
-            }
-
-            if diff.x.abs() > diff.y.abs() {
-                other.x += if diff.x > 0 { 1 } else { -1 };
-                other.y += diff.y;
-                return;
-            } else {
-                other.x += diff.x;
-                other.y += if diff.y > 0 { 1 } else { -1 };
-                return;
-            }
+        if diff.x.abs().max(diff.y.abs()) <= rope.slack {
+            return false;
         }
 
-        panic!("Invalid operation (moved knots in more steps than one).");
+        if rope.diagonal {
+            other.x += diff.x.signum();
+            other.y += diff.y.signum();
+        } else if diff.x.abs() >= diff.y.abs() {
+            other.x += diff.x.signum();
+        } else {
+            other.y += diff.y.signum();
+        }
+
+        true
     }
 }
 
 #[derive(Clone, Copy)]
-enum Move {
-    Left(i32),
-    Up(i32),
-    Right(i32),
-    Down(i32)
+struct Rope {
+    knots: usize,
+    // How far apart, counting diagonals as one, two knots can be before the second one follows
+    slack: i32,
+    // Whether a knot may catch up diagonally or only along rows and columns
+    diagonal: bool
+}
+
+impl Rope {
+    const SHORT: Rope = Rope { knots: 2, slack: 1, diagonal: true };
+    const LONG: Rope = Rope { knots: 10, slack: 1, diagonal: true };
+}
+
+// The head moves `amount` steps of (dx, dy), diagonal moves such as `UL 3` change both
+#[derive(Clone, Copy)]
+struct Move {
+    dx: i32,
+    dy: i32,
+    amount: i32
+}
+
+// Everything a single knot did
+#[derive(Default)]
+struct History {
+    // How many times the knot stepped onto every cell it visited, the start included
+    visits: HashMap<Knot, usize>,
+    path_length: usize,
+    // Top left and bottom right corners of the area it went through
+    min: Knot,
+    max: Knot
+}
+
+impl History {
+    fn record(&mut self, knot: Knot, moved: bool) {
+        *self.visits.entry(knot).or_insert(0) += 1;
+
+        if moved {
+            self.path_length += 1;
+        }
+
+        self.min = Knot { x: self.min.x.min(knot.x), y: self.min.y.min(knot.y) };
+        self.max = Knot { x: self.max.x.max(knot.x), y: self.max.y.max(knot.y) };
+    }
+}
+
+// Always keeps the cells the tail visited, and the full history of the knots asked for
+struct Tracker {
+    visited: HashSet<Knot>,
+    histories: Vec<Option<History>>
+}
+
+impl Tracker {
+    fn new(knots: usize, tracked: &[usize]) -> Self {
+        let mut histories: Vec<Option<History>> = (0..knots).map(|_| None).collect();
+
+        for &knot in tracked {
+            let mut history = History::default();
+            history.record(Knot::default(), false);
+            histories[knot] = Some(history);
+        }
+
+        Self { visited: HashSet::from([Knot::default()]), histories }
+    }
+
+    fn all(knots: usize) -> Self {
+        Self::new(knots, &(0..knots).collect::<Vec<usize>>())
+    }
+
+    fn record(&mut self, index: usize, knot: Knot) {
+        if index == self.histories.len() - 1 {
+            self.visited.insert(knot);
+        }
+
+        if let Some(history) = &mut self.histories[index] {
+            history.record(knot, true);
+        }
+    }
+
+    fn history(&self, index: usize) -> Option<&History> {
+        self.histories.get(index)?.as_ref()
+    }
 }
 
 fn main() {
     let input = include_str!("../input/day09.input");
-    
-    // Part one
-    let visited_short = first_puzzle(input);
-    println!("The tail of the short rope visited {} positions at least once.", visited_short);
 
-    // Part two
-    let visited_long = second_puzzle(input);
-    println!("The tail of the long rope visited {} positions at least once.", visited_long);
+    Day::new("day09", &[
+        "The tail of the short rope visited {} positions at least once.",
+        "The tail of the long rope visited {} positions at least once.",
+    ])
+    .solution(Solution::new("morgan").part(first_puzzle).part(second_puzzle))
+    .report("rope", print_rope)
+    .report("history", print_history)
+    .run(input);
 }
 
 #[test]
@@ -67,85 +141,167 @@ fn sample() {
     assert_eq!(second_puzzle(input), 1);
 }
 
+#[test]
+fn physics() {
+    let larger = "R 5\nU 8\nL 8\nD 3\nR 17\nD 10\nL 25\nU 20";
+    assert_eq!(second_puzzle(larger), 36);
+    assert_eq!(solve(larger, Rope { knots: 10, ..Rope::SHORT }), 36);
+
+    // A lone knot is its own tail and goes everywhere the head goes
+    assert_eq!(solve("R 4\nUL 2", Rope { knots: 1, ..Rope::SHORT }), 7);
+
+    // Diagonal moves, with and without diagonal catch-up
+    assert_eq!(solve("UR 3", Rope::SHORT), 3);
+    assert_eq!(solve("UR 2", Rope { diagonal: false, ..Rope::SHORT }), 3);
+    assert_eq!(solve("DL 2\nLD 1", Rope::SHORT), 3);
+
+    // More slack means the tail moves less
+    assert_eq!(solve("R 4", Rope { slack: 3, ..Rope::SHORT }), 2);
+    assert!(solve(larger, Rope { slack: 2, ..Rope::LONG }) < 36);
+
+    assert_eq!(parse_knots(None, 2), Ok(2));
+    assert_eq!(parse_knots(Some(&"3".to_string()), 2), Ok(3));
+    assert!(parse_knots(Some(&"0".to_string()), 2).is_err());
+    assert!(parse_knots(Some(&"-1".to_string()), 2).is_err());
+}
+
+#[test]
+fn histories() {
+    let input = include_str!("../sample/day09.input");
+
+    let mut tracker = Tracker::all(Rope::LONG.knots);
+    simulate(input, Rope::LONG, &mut tracker);
+
+    let head = tracker.history(0).unwrap();
+    assert_eq!(head.path_length, 24);
+    assert_eq!((head.min, head.max), (Knot { x: 0, y: -4 }, Knot { x: 5, y: 0 }));
+    assert_eq!(head.visits[&Knot { x: 2, y: -2 }], 2);
+
+    // The knot right behind the head moves just like the tail of the short rope
+    assert_eq!(tracker.history(1).unwrap().visits.len(), first_puzzle(input));
+    assert_eq!(tracker.history(9).unwrap().visits.len(), tracker.visited.len());
+    assert_eq!(tracker.history(9).unwrap().path_length, 0);
+
+    let tracker = Tracker::new(Rope::LONG.knots, &[3]);
+    assert!(tracker.history(0).is_none() && tracker.history(3).is_some() && tracker.history(10).is_none());
+}
+
 fn first_puzzle(source: &str) -> usize {
-    solve(source, &mut [Knot::default(); 2])
+    solve(source, Rope::SHORT)
 }
 
 fn second_puzzle(source: &str) -> usize {
-    solve(source, &mut [Knot::default(); 10])
+    solve(source, Rope::LONG)
 }
 
-fn solve(source: &str, rope: &mut [Knot]) -> usize {
-    let mut visited = vec![Knot::default()];
+fn solve(source: &str, config: Rope) -> usize {
+    assert!(config.knots > 0, "A rope needs at least one knot.");
+    assert!(config.slack >= 0, "The slack can't be negative.");
+
+    let mut tracker = Tracker::new(config.knots, &[]);
+    simulate(source, config, &mut tracker);
+
+    tracker.visited.len()
+}
+
+fn simulate(source: &str, config: Rope, tracker: &mut Tracker) {
+    let mut rope = vec![Knot::default(); config.knots];
 
     source
         .lines()
         .map(parse_move)
-        .for_each(|direction| move_rope(direction, &mut visited, rope));
-
-    visited.len()
+        .for_each(|direction| move_rope(direction, tracker, &mut rope, &config));
 }
 
 fn parse_move(source: &str) -> Move {
     let mut parts = source.split(' ');
-    let letter = parts.next().expect("Could not find a valid direction.");
+    let letters = parts.next().expect("Could not find a valid direction.");
     let amount = parts.next().expect("Could not find a valid amount.").parse::<i32>().expect("Invalid number");
 
-    match letter {
-        "L" => Move::Left(amount),
-        "U" => Move::Up(amount),
-        "R" => Move::Right(amount),
-        "D" => Move::Down(amount),
-        _ => panic!("Could not find a valid direction.")
+    let (mut dx, mut dy) = (0, 0);
+
+    for letter in letters.chars() {
+        match letter {
+            'L' if dx == 0 => dx = -1,
+            'R' if dx == 0 => dx = 1,
+            'U' if dy == 0 => dy = -1,
+            'D' if dy == 0 => dy = 1,
+            _ => panic!("Could not find a valid direction.")
+        }
     }
+
+    assert!(dx != 0 || dy != 0, "Could not find a valid direction.");
+    Move { dx, dy, amount }
 }
 
-fn move_rope(direction: Move, visited: &mut Vec<Knot>, rope: &mut [Knot]) {
-    match direction {
-        Move::Left(amount) => {
-            for _ in 0..amount {
-                rope[0].x -= 1;
-                drag_rope(visited, rope);
-            }
-        },
-        Move::Up(amount) => {
-            for _ in 0..amount {
-                rope[0].y -= 1;
-                drag_rope(visited, rope);
-            }
-        },
-        Move::Right(amount) => {
-            for _ in 0..amount {
-                rope[0].x += 1;
-                drag_rope(visited, rope);
-            }
-        },
-        Move::Down(amount) => {
-            for _ in 0..amount {
-                rope[0].y += 1;
-                drag_rope(visited, rope);
-            }
-        },
+fn move_rope(direction: Move, tracker: &mut Tracker, rope: &mut [Knot], config: &Rope) {
+    for _ in 0..direction.amount {
+        rope[0].x += direction.dx;
+        rope[0].y += direction.dy;
+
+        tracker.record(0, rope[0]);
+        drag_rope(tracker, rope, config);
     }
 }
 
-fn drag_rope(visited: &mut Vec<Knot>, rope: &mut [Knot]) {
+fn drag_rope(tracker: &mut Tracker, rope: &mut [Knot], config: &Rope) {
     for i in 1..rope.len() {
         let first = rope[i-1];
-        let second = rope[i];
 
-        if first == second {
-            continue;
+        // Without diagonal catch-up a knot may need a couple of steps to get back in range
+        while first.drag(&mut rope[i], config) {
+            tracker.record(i, rope[i]);
         }
+    }
+}
 
-        let distance = first.diff(&second);
-        if distance.x.abs() > 1 || distance.y.abs() > 1 {
-            first.drag(&mut rope[i]);
-            //rope[i] = second;
+// `--report history [knots] [knot...]`, every knot is tracked unless some are picked
+fn print_history(source: &str, args: &[String]) -> Result<(), String> {
+    let knots = parse_knots(args.first(), Rope::LONG.knots)?;
+    let config = Rope { knots, ..Rope::LONG };
 
-            if i == rope.len() - 1 && !visited.contains(&rope[i]) {
-                visited.push(rope[i]);
-            }
-        }
+    let picked: Vec<usize> = args
+        .iter()
+        .skip(1)
+        .map(|knot| knot.parse::<usize>().ok().filter(|knot| *knot < knots).ok_or_else(|| format!("Invalid knot '{}'.", knot)))
+        .collect::<Result<_, _>>()?;
+
+    let mut tracker = if picked.is_empty() { Tracker::all(knots) } else { Tracker::new(knots, &picked) };
+    simulate(source, config, &mut tracker);
+
+    println!("{:>5}  {:>8}  {:>8}  {:>24}  Most visited", "Knot", "Cells", "Steps", "Bounding box");
+    for knot in 0..knots {
+        let Some(history) = tracker.history(knot) else { continue };
+        let (cell, visits) = history.visits.iter().max_by_key(|(cell, visits)| (**visits, -cell.y, -cell.x)).unwrap();
+        let bounds = format!("({}, {})..({}, {})", history.min.x, history.min.y, history.max.x, history.max.y);
+
+        println!("{:>5}  {:>8}  {:>8}  {:>24}  ({}, {}) {} times", knot, history.visits.len(), history.path_length, bounds, cell.x, cell.y, visits);
     }
-}
\ No newline at end of file
+
+    Ok(())
+}
+
+// `--report rope [knots] [slack] [orthogonal]`
+fn print_rope(source: &str, args: &[String]) -> Result<(), String> {
+    let slack = match args.get(1) {
+        Some(slack) => slack.parse::<i32>().ok().filter(|slack| *slack >= 0).ok_or_else(|| format!("The slack must be a non-negative number, got '{}'.", slack))?,
+        None => 1
+    };
+
+    let config = Rope {
+        knots: parse_knots(args.first(), Rope::SHORT.knots)?,
+        slack,
+        diagonal: args.get(2).map(|arg| arg.as_str()) != Some("orthogonal")
+    };
+
+    println!("The tail of a {} knot rope visited {} positions at least once.", config.knots, solve(source, config));
+    Ok(())
+}
+
+// A rope needs at least one knot
+fn parse_knots(arg: Option<&String>, default: usize) -> Result<usize, String> {
+    match arg {
+        Some(knots) => knots.parse::<usize>().ok().filter(|knots| *knots > 0).ok_or_else(|| format!("The number of knots must be at least 1, got '{}'.", knots)),
+        None => Ok(default)
+    }
+}
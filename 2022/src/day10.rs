@@ -1,10 +1,169 @@
+use std::{collections::HashMap, fs};
+use adventofcode_2022::runner::{Day, Solution};
+
 #[derive(Debug)]
 struct Cpu<T> where T: Clocked {
-    register: i32,
+    // The first register is `x`, the one peripherals see
+    registers: Vec<i32>,
     cycles: usize,
     clocked: T
 }
 
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Operand {
+    Register,
+    Immediate,
+    Label
+}
+
+// Where execution goes after an instruction
+enum Flow {
+    Next,
+    Jump(usize)
+}
+
+// An instruction's effect only applies once all of its cycles are over. It gets the registers and
+// the instruction's operands: register indices, immediate values and instruction indices for labels.
+struct Opcode {
+    name: &'static str,
+    cycles: usize,
+    operands: &'static [Operand],
+    effect: fn(&mut Vec<i32>, &[i32]) -> Flow
+}
+
+const INSTRUCTIONS: &[Opcode] = &[
+    Opcode { name: "noop", cycles: 1, operands: &[], effect: |_, _| Flow::Next },
+    Opcode { name: "addx", cycles: 2, operands: &[Operand::Immediate], effect: |registers, operands| {
+        registers[0] += operands[0];
+        Flow::Next
+    }},
+    Opcode { name: "add", cycles: 2, operands: &[Operand::Register, Operand::Immediate], effect: |registers, operands| {
+        registers[operands[0] as usize] += operands[1];
+        Flow::Next
+    }},
+    Opcode { name: "set", cycles: 1, operands: &[Operand::Register, Operand::Immediate], effect: |registers, operands| {
+        registers[operands[0] as usize] = operands[1];
+        Flow::Next
+    }},
+    Opcode { name: "jmp", cycles: 1, operands: &[Operand::Label], effect: |_, operands| Flow::Jump(operands[0] as usize) },
+    Opcode { name: "jnz", cycles: 2, operands: &[Operand::Register, Operand::Label], effect: |registers, operands| {
+        if registers[operands[0] as usize] != 0 { Flow::Jump(operands[1] as usize) } else { Flow::Next }
+    }},
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Instruction {
+    // Index into `INSTRUCTIONS`
+    opcode: usize,
+    operands: Vec<i32>
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Program {
+    instructions: Vec<Instruction>,
+    // Register names by index, `x` always comes first
+    registers: Vec<String>,
+    labels: Vec<(String, usize)>
+}
+
+impl Program {
+    // Reads one instruction per line. `name:` puts a label on the next instruction and `#` starts a comment.
+    fn assemble(source: &str) -> Program {
+        let lines: Vec<&str> = source
+            .lines()
+            .map(|line| line.split('#').next().unwrap().trim())
+            .filter(|line| !line.is_empty())
+            .collect();
+
+        // Labels may be used before they're defined
+        let mut labels = vec![];
+        let mut count = 0;
+        for line in &lines {
+            match line.strip_suffix(':') {
+                Some(label) => {
+                    assert!(labels.iter().all(|(name, _)| name != label), "Label defined twice: {}", label);
+                    labels.push((label.to_string(), count));
+                }
+                None => count += 1
+            }
+        }
+
+        let mut registers = vec!["x".to_string()];
+        let mut instructions = vec![];
+
+        for line in lines.iter().filter(|line| !line.ends_with(':')) {
+            let mut words = line.split_whitespace();
+            let name = words.next().unwrap();
+            let opcode = INSTRUCTIONS
+                .iter()
+                .position(|opcode| opcode.name == name)
+                .unwrap_or_else(|| panic!("Invalid instruction: {}", line));
+
+            let words: Vec<&str> = words.collect();
+            assert_eq!(words.len(), INSTRUCTIONS[opcode].operands.len(), "Wrong number of operands: {}", line);
+
+            let operands = INSTRUCTIONS[opcode].operands
+                .iter()
+                .zip(words)
+                .map(|(operand, word)| match operand {
+                    Operand::Immediate => word.parse::<i32>().expect("Invalid number."),
+                    Operand::Label => labels
+                        .iter()
+                        .find(|(name, _)| name == word)
+                        .unwrap_or_else(|| panic!("Unknown label: {}", word)).1 as i32,
+                    Operand::Register => match registers.iter().position(|register| register == word) {
+                        Some(index) => index as i32,
+                        None => {
+                            registers.push(word.to_string());
+                            registers.len() as i32 - 1
+                        }
+                    }
+                })
+                .collect();
+
+            instructions.push(Instruction { opcode, operands });
+        }
+
+        Program { instructions, registers, labels }
+    }
+
+    // Turns the program back into text that assembles to the same program
+    fn disassemble(&self) -> String {
+        let mut labels: HashMap<usize, Vec<&str>> = HashMap::new();
+        for (name, index) in &self.labels {
+            labels.entry(*index).or_default().push(name);
+        }
+
+        let mut lines = vec![];
+        let label = |index: usize, lines: &mut Vec<String>| {
+            for name in labels.get(&index).into_iter().flatten() {
+                lines.push(format!("{}:", name));
+            }
+        };
+
+        for (index, instruction) in self.instructions.iter().enumerate() {
+            label(index, &mut lines);
+
+            let opcode = &INSTRUCTIONS[instruction.opcode];
+            let mut words = vec![opcode.name.to_string()];
+
+            for (operand, value) in opcode.operands.iter().zip(&instruction.operands) {
+                words.push(match operand {
+                    Operand::Immediate => value.to_string(),
+                    Operand::Register => self.registers[*value as usize].clone(),
+                    Operand::Label => labels[&(*value as usize)][0].to_string()
+                });
+            }
+
+            lines.push(words.join(" "));
+        }
+
+        // Labels can also point right past the last instruction
+        label(self.instructions.len(), &mut lines);
+        lines.join("\n")
+    }
+}
+
 trait Clocked {
     fn run_cycle(&mut self, cycles: usize, register: i32);
 }
@@ -22,9 +181,17 @@ impl Clocked for SignalAdder {
     }
 }
 
+// Draws one pixel per cycle, the screen holds every row drawn so far
 struct Lcd {
     sprite_size: i32,
-    row_size: usize
+    row_size: usize,
+    screen: String
+}
+
+impl Lcd {
+    fn new(sprite_size: i32, row_size: usize) -> Self {
+        Self { sprite_size, row_size, screen: String::new() }
+    }
 }
 
 impl Clocked for Lcd {
@@ -33,14 +200,14 @@ impl Clocked for Lcd {
         let sprite_start = register - 1;
         let sprite_end = register - 1 + self.sprite_size;
 
-        if position == 0 {
-            println!();
+        if position == 0 && cycles > 1 {
+            self.screen.push('\n');
         }
 
         if sprite_start <= position && sprite_end > position {
-            print!("#");
+            self.screen.push('#');
         } else {
-            print!(".");
+            self.screen.push('.');
         }
     }
 }
@@ -48,51 +215,47 @@ impl Clocked for Lcd {
 impl<T> Cpu<T> where T: Clocked {
     fn new(clocked: T) -> Cpu<T> {
         Cpu {
-            register: 1,
+            registers: vec![1],
             cycles: 0,
             clocked
         }
     }
 
     fn parse(&mut self, source: &str) {
-        source
-            .lines()
-            .for_each(|line| {
-                if line.starts_with("noop") {
-                    self.noop();
-                } else if line.starts_with("addx ") {
-                    let x = line.strip_prefix("addx ").unwrap().parse::<i32>().expect("Invalid number.");
-                    self.addx(x);
-                } else {
-                    panic!("Invalid instruction: {}", line);
-                }
-            });
+        self.run(&Program::assemble(source));
     }
 
-    fn noop(&mut self) {
-        self.cycles += 1;
-        self.clocked.run_cycle(self.cycles, self.register);
-    }
+    // Runs until execution leaves the program, which never happens if it loops forever
+    fn run(&mut self, program: &Program) {
+        self.registers.resize(program.registers.len().max(1), 0);
+        let mut pointer = 0;
+
+        while let Some(instruction) = program.instructions.get(pointer) {
+            let opcode = &INSTRUCTIONS[instruction.opcode];
 
-    fn addx(&mut self, x: i32) {
-        self.cycles += 1;
-        self.clocked.run_cycle(self.cycles, self.register);
+            for _ in 0..opcode.cycles {
+                self.cycles += 1;
+                self.clocked.run_cycle(self.cycles, self.registers[0]);
+            }
 
-        self.cycles += 1;
-        self.clocked.run_cycle(self.cycles, self.register);
-        self.register += x;
+            pointer = match (opcode.effect)(&mut self.registers, &instruction.operands) {
+                Flow::Next => pointer + 1,
+                Flow::Jump(target) => target
+            };
+        }
     }
 }
 
 fn main() {
     let input = include_str!("../input/day10.input");
 
-    // Part one
-    let signal_sum = first_puzzle(input);
-    println!("The sum of the six signal strengths is {}.", signal_sum);
-
-    // Part two
-    second_puzzle(input);
+    Day::new("day10", &[
+        "The sum of the six signal strengths is {}.",
+        "The CRT shows:\n{}",
+    ])
+    .solution(Solution::new("morgan").part(first_puzzle).part(second_puzzle))
+    .report("asm", print_assembly)
+    .run(input);
 }
 
 #[test]
@@ -100,7 +263,39 @@ fn sample() {
     let input = include_str!("../sample/day10.input");
 
     assert_eq!(first_puzzle(input), 13140);
-    second_puzzle(input);
+
+    let screen = second_puzzle(input);
+    assert_eq!(screen.lines().count(), 6);
+    assert_eq!(screen.lines().next(), Some("##..##..##..##..##..##..##..##..##..##.."));
+    assert_eq!(screen.lines().last(), Some("#######.......#######.......#######....."));
+}
+
+#[test]
+fn assembler() {
+    let program = Program::assemble("
+        set y 3    # loop three times
+        loop:
+        addx 2
+        add y -1
+        jnz y loop
+        jmp end
+        addx 100
+        end:
+    ");
+
+    assert_eq!(program.registers, vec!["x", "y"]);
+    assert_eq!(program.labels, vec![("loop".to_string(), 1), ("end".to_string(), 6)]);
+    assert_eq!(program.disassemble(), "set y 3\nloop:\naddx 2\nadd y -1\njnz y loop\njmp end\naddx 100\nend:");
+    assert_eq!(Program::assemble(&program.disassemble()), program);
+
+    let mut cpu = Cpu::new(SignalAdder::default());
+    cpu.run(&program);
+    assert_eq!(cpu.registers, vec![7, 0]);
+    assert_eq!(cpu.cycles, 1 + 3 * 6 + 1);
+
+    // The puzzle's programs read the same through the assembler
+    let sample = include_str!("../sample/day10.input");
+    assert_eq!(Program::assemble(sample).disassemble(), sample.trim_end());
 }
 
 fn first_puzzle(source: &str) -> i32 {
@@ -110,10 +305,32 @@ fn first_puzzle(source: &str) -> i32 {
     cpu.clocked.signal
 }
 
-fn second_puzzle(source: &str) {
-    let mut cpu = Cpu::new(Lcd { sprite_size: 3, row_size: 40 });
+fn second_puzzle(source: &str) -> String {
+    let mut cpu = Cpu::new(Lcd::new(3, 40));
     cpu.parse(source);
 
+    cpu.clocked.screen
+}
+
+// Prints the input or the given file as the disassembler sees it, then runs it,
+// `--report asm [file]`
+fn print_assembly(source: &str, args: &[String]) -> Result<(), String> {
+    let source = match args.first() {
+        Some(path) => fs::read_to_string(path).map_err(|error| format!("Could not read {}: {}", path, error))?,
+        None => source.to_string()
+    };
+
+    let program = Program::assemble(&source);
+    println!("{}", program.disassemble());
+
+    let mut cpu = Cpu::new(SignalAdder::default());
+    cpu.run(&program);
+
     println!();
-    println!();
-}
\ No newline at end of file
+    println!("Ran for {} cycles, signal strength {}", cpu.cycles, cpu.clocked.signal);
+    for (name, value) in program.registers.iter().zip(&cpu.registers) {
+        println!("  {} = {}", name, value);
+    }
+
+    Ok(())
+}
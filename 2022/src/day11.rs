@@ -1,3 +1,5 @@
+use adventofcode_2022::runner::{Day, Solution};
+
 struct MonkeyTurn {
     monkey_num: usize,
     starting_items: Vec<u64>,
@@ -11,11 +13,12 @@ struct MonkeyTurn {
 fn main() {
     let input = include_str!("../input/day11.input");
 
-    let monkey_business = first_puzzle(input);
-    println!("The level of monkey business after 20 rounds is {}.", monkey_business);
-
-    let monkey_business_hard = second_puzzle(input);
-    println!("The level of monkey business after 10000 rounds is {}.", monkey_business_hard);
+    Day::new("day11", &[
+        "The level of monkey business after 20 rounds is {}.",
+        "The level of monkey business after 10000 rounds is {}.",
+    ])
+    .solution(Solution::new("raquel").part(first_puzzle).part(second_puzzle))
+    .run(input);
 }
 
 #[test]
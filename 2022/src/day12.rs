@@ -1,4 +1,5 @@
 use std::collections::VecDeque;
+use adventofcode_2022::{profile, runner::{Day, Solution}};
 
 const LEFT: usize = 0;
 const UP: usize = 1;
@@ -53,11 +54,12 @@ impl<T> PointAddressable for Vec<Vec<T>> {
 fn main() {
     let input = include_str!("../input/day12.input");
 
-    let steps_start = first_puzzle(input);
-    println!("The goal can be reached from the start in as little as {} steps.", steps_start);
-
-    let steps_a = second_puzzle(input);
-    println!("The goal can be reached from any square with 'a' elevation in as little as {} steps.", steps_a);
+    Day::new("day12", &[
+        "The goal can be reached from the start in as little as {} steps.",
+        "The goal can be reached from any square with 'a' elevation in as little as {} steps.",
+    ])
+    .solution(Solution::new("raquel").part(first_puzzle).part(second_puzzle))
+    .run(input);
 }
 
 #[test]
@@ -69,12 +71,12 @@ fn sample() {
 }
 
 fn first_puzzle(source: &str) -> usize {
-    let (map, start, end) = parse_map(source);
+    let (map, start, end) = profile::phase("parse", || parse_map(source));
     shortest_path(&map, &start, &end).unwrap()
 }
 
 fn second_puzzle(source: &str) -> usize {
-    let (map, _, end) = parse_map(source);
+    let (map, _, end) = profile::phase("parse", || parse_map(source));
     let from_elevation = elevation('a');
 
     let mut paths = Vec::new();
@@ -1,5 +1,6 @@
 use std::fmt::{Display, Formatter, Result};
 use std::cmp::Ordering;
+use adventofcode_2022::{runner::{Day, Solution}, trace};
 
 #[derive(PartialEq, Eq)]
 enum Item {
@@ -85,11 +86,12 @@ impl Display for Item {
 fn main() {
     let input = include_str!("../input/day13.input");
 
-    let sum = first_puzzle(input);
-    println!("The sum of the indices of the well-ordered pairs is {}.", sum);
-
-    let decoder_key = second_puzzle(input);
-    println!("The decoder key for the distress signal is {}.", decoder_key);
+    Day::new("day13", &[
+        "The sum of the indices of the well-ordered pairs is {}.",
+        "The decoder key for the distress signal is {}.",
+    ])
+    .solution(Solution::new("raquel").part(first_puzzle).part(second_puzzle))
+    .run(input);
 }
 
 #[test]
@@ -197,7 +199,7 @@ fn parse_line(source: &str) -> Item {
             let top = stack.last_mut().unwrap();
             
             top.push(Item::Integer(number));
-            //println!("{}", top);
+            trace!(Trace, "parsed {} into {}", number, top);
             continue;
         }
 
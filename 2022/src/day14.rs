@@ -1,3 +1,5 @@
+use adventofcode_2022::runner::{Day, Solution};
+
 const AIR: u8 = 0;
 const ROCK: u8 = 1;
 const SAND: u8 = 2;
@@ -31,13 +33,12 @@ impl Point {
 fn main() {
     let input = include_str!("../input/day14.input");
 
-    // Part one
-    let sand_void = first_puzzle(input);
-    println!("There are {} units of sand accumulated before it starts falling into the abyss.", sand_void);
-
-    // Part two
-    let sand_floor = second_puzzle(input);
-    println!("There are {} units of sand accumulated when there is a floor.", sand_floor);
+    Day::new("day14", &[
+        "There are {} units of sand accumulated before it starts falling into the abyss.",
+        "There are {} units of sand accumulated when there is a floor.",
+    ])
+    .solution(Solution::new("raquel").part(first_puzzle).part(second_puzzle))
+    .run(input);
 }
 
 #[test]
@@ -1,13 +1,16 @@
+use adventofcode_2022::runner::{Day, Solution};
+
 type Position = (i64, i64);
 
 fn main() {
     let input = include_str!("../input/day15.input");
 
-    let beacon_not_here = first_puzzle(input, 2000000);
-    println!("In the row at y=2000000, the beacon can't be in {} positions.", beacon_not_here);
-
-    let tuning_frequency = second_puzzle(input, 4000000);
-    println!("The tuning frequency of the distress signal is {}.", tuning_frequency);
+    Day::new("day15", &[
+        "In the row at y=2000000, the beacon can't be in {} positions.",
+        "The tuning frequency of the distress signal is {}.",
+    ])
+    .solution(Solution::new("morgan").part(|source| first_puzzle(source, 2000000)).part(|source| second_puzzle(source, 4000000)))
+    .run(input);
 }
 
 #[test]
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use adventofcode_2022::{profile, progress, runner::{Day, Solution}, trace};
 
 struct Valve {
     index: usize,
@@ -10,13 +11,13 @@ struct Valve {
 fn main() {
     let input = include_str!("../input/day16.input");
 
-    // Part one
-    let without_elephant = first_puzzle(input);
-    println!("Without an elephant helping you, you can release at most {} pressure in 30 minutes.", without_elephant);
-
-    // Part two
-    let with_elephant = second_puzzle(input);
-    println!("With an elephant helping you, you can release at most {} pressure in 26 minutes.", with_elephant);
+    Day::new("day16", &[
+        "Without an elephant helping you, you can release at most {} pressure in 30 minutes.",
+        "With an elephant helping you, you can release at most {} pressure in 26 minutes.",
+    ])
+    .solution(Solution::new("raquel").part(first_puzzle).part(second_puzzle))
+    .solution(Solution::new("pairs").part(pairs::first_puzzle).part(pairs::second_puzzle))
+    .run(input);
 }
 
 #[test]
@@ -25,10 +26,13 @@ fn sample() {
 
     assert_eq!(first_puzzle(sample), 1651);
     //assert_eq!(second_puzzle(sample), 1707);
+
+    assert_eq!(pairs::first_puzzle(sample), 1651);
+    assert_eq!(pairs::second_puzzle(sample), 1707);
 }
 
 fn first_puzzle(source: &str) -> u64 {
-    let valves = parse_valves(source);
+    let valves = profile::phase("parse", || parse_valves(source));
 
     let mut open = 0;
     for valve in valves.values() {
@@ -44,7 +48,7 @@ fn first_puzzle(source: &str) -> u64 {
 }
 
 fn second_puzzle(source: &str) -> u64 {
-    let valves = parse_valves(source);
+    let valves = profile::phase("parse", || parse_valves(source));
 
     let mut open = 0;
     for valve in valves.values() {
@@ -67,11 +71,14 @@ fn dfs(
     rate: u64, 
     minutes: u64
 ) -> u64 {
-    if minutes == 0 {
+    if minutes == 0 || progress::cancelled() {
         return 0;
     }
 
+    progress::explore();
+
     if let Some(&released) = cache.get(&(name.to_owned(), open, minutes)) {
+        trace!(Trace, "cache hit at {} with {} minutes left", name, minutes);
         return released;
     }
 
@@ -95,6 +102,7 @@ fn dfs(
     }
 
     cache.insert((name.to_owned(), open, minutes), released);
+    progress::cache_size(cache.len());
     released
 }
 
@@ -107,11 +115,14 @@ fn dfs_with_elephant(
     rate: u64, 
     minutes: u64
 ) -> u64 {
-    if minutes == 0 {
+    if minutes == 0 || progress::cancelled() {
         return 0;
     }
 
+    progress::explore();
+
     if let Some(&released) = cache.get(&(hu_at.to_owned(), el_at.to_owned(), open, minutes)) {
+        trace!(Trace, "cache hit at {} and {} with {} minutes left", hu_at, el_at, minutes);
         return released;
     }
 
@@ -195,9 +206,116 @@ fn dfs_with_elephant(
     }
 
     cache.insert((hu_at.to_owned(), el_at.to_owned(), open, minutes), released);
+    progress::cache_size(cache.len());
     released
 }
 
+mod pairs {
+    use std::collections::{HashMap, VecDeque};
+    use super::{parse_valves, profile, progress, Valve};
+
+    // Only valves with a non-zero flow are worth walking to, so the search jumps straight
+    // between them using the shortest distances, recording the best release per set of opened valves.
+    struct Network {
+        flows: Vec<u64>,
+        distances: Vec<Vec<u64>>,
+        useful: Vec<usize>,
+        // Whether a single route is the answer, otherwise its pressure is no progress to report
+        alone: bool,
+    }
+
+    pub fn first_puzzle(source: &str) -> u64 {
+        let best = best_per_set(source, 30, true);
+
+        best.values().copied().max().unwrap_or(0)
+    }
+
+    pub fn second_puzzle(source: &str) -> u64 {
+        let best = best_per_set(source, 26, false);
+        let mut released = 0;
+
+        for (hu_set, hu_released) in &best {
+            if progress::cancelled() {
+                break;
+            }
+
+            for (el_set, el_released) in &best {
+                if hu_set & el_set == 0 && hu_released + el_released > released {
+                    released = hu_released + el_released;
+                    progress::best(released);
+                }
+            }
+        }
+
+        released
+    }
+
+    fn best_per_set(source: &str, minutes: u64, alone: bool) -> HashMap<u64, u64> {
+        let valves = profile::phase("parse", || parse_valves(source));
+
+        let mut by_index: Vec<&Valve> = valves.values().collect();
+        by_index.sort_by_key(|valve| valve.index);
+
+        let network = Network {
+            flows: by_index.iter().map(|valve| valve.flow).collect(),
+            distances: by_index.iter().map(|valve| distances_from(&valves, valve)).collect(),
+            useful: by_index.iter().filter(|valve| valve.flow > 0).map(|valve| valve.index).collect(),
+            alone,
+        };
+
+        let mut best = HashMap::new();
+        visit(&network, &mut best, valves["AA"].index, 0, 0, minutes);
+
+        best
+    }
+
+    fn visit(network: &Network, best: &mut HashMap<u64, u64>, at: usize, opened: u64, released: u64, minutes: u64) {
+        if progress::cancelled() {
+            return;
+        }
+
+        progress::explore();
+        if network.alone {
+            progress::best(released);
+        }
+
+        let entry = best.entry(opened).or_insert(0);
+        *entry = released.max(*entry);
+
+        for (bit, &target) in network.useful.iter().enumerate() {
+            // Walking there and opening the valve
+            let cost = network.distances[at][target].saturating_add(1);
+
+            if opened & (1 << bit) != 0 || cost >= minutes {
+                continue;
+            }
+
+            let remaining = minutes - cost;
+            visit(network, best, target, opened | 1 << bit, released + network.flows[target] * remaining, remaining);
+        }
+    }
+
+    fn distances_from(valves: &HashMap<String, Valve>, start: &Valve) -> Vec<u64> {
+        let mut distances = vec![u64::MAX; valves.len()];
+        let mut queue = VecDeque::from([(start, 0)]);
+
+        distances[start.index] = 0;
+
+        while let Some((valve, distance)) = queue.pop_front() {
+            for tunnel in &valve.tunnels {
+                let next = &valves[tunnel];
+
+                if distances[next.index] == u64::MAX {
+                    distances[next.index] = distance + 1;
+                    queue.push_back((next, distance + 1));
+                }
+            }
+        }
+
+        distances
+    }
+}
+
 fn parse_valves(source: &str) -> HashMap<String, Valve> {
     let mut valves = HashMap::<String, Valve>::new();
     let mut index = 0;
@@ -1,4 +1,5 @@
 use std::{collections::HashMap, vec};
+use adventofcode_2022::{runner::{Day, Solution}, trace};
 
 #[derive(Clone, Copy)]
 enum BlockReason {
@@ -90,13 +91,12 @@ struct Map {
 fn main() {
     let input = include_str!("../input/day17.input");
 
-    // Part one
-    let height_small = solve(input, 2022);
-    println!("The tower of rocks is {} units tall after 2022 rocks have stopped falling.", height_small);
-
-    // Part two
-    let height_large = solve(input, 1000000000000);
-    println!("The tower of rocks is {} units tall after 1000000000000 rocks have stopped falling.", height_large);
+    Day::new("day17", &[
+        "The tower of rocks is {} units tall after 2022 rocks have stopped falling.",
+        "The tower of rocks is {} units tall after 1000000000000 rocks have stopped falling.",
+    ])
+    .solution(Solution::new("raquel").part(|source| solve(source, 2022)).part(|source| solve(source, 1000000000000)))
+    .run(input);
 }
 
 #[test]
@@ -228,6 +228,12 @@ fn solve(source: &str, limit: u64) -> u64 {
 
             top += loop_height * times as i64 + (leftover_height - loop_height_min);
 
+            trace!(
+                Debug,
+                "cycle found: rocks {}..{} add {} units, repeated {} times with {} rocks left over",
+                loop_index_start, loop_index_end, loop_height, times, leftover_rocks
+            );
+
             break;
         }
 
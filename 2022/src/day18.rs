@@ -1,4 +1,5 @@
 use std::{collections::{HashMap, VecDeque, HashSet}, hash::Hash};
+use adventofcode_2022::{profile, runner::{Day, Solution}};
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 struct Vector3 {
@@ -33,13 +34,12 @@ impl Vector3 {
 fn main() {
     let input = include_str!("../input/day18.input");
 
-    // Part one
-    let surface_area = first_puzzle(input);
-    println!("The surface area of the scanned lava droplet is {}.", surface_area);
-
-    // Part two
-    let surface_area_revised = second_puzzle(input);
-    println!("The revised surface area of the scanned lava droplet is {}.", surface_area_revised);
+    Day::new("day18", &[
+        "The surface area of the scanned lava droplet is {}.",
+        "The revised surface area of the scanned lava droplet is {}.",
+    ])
+    .solution(Solution::new("raquel").part(first_puzzle).part(second_puzzle))
+    .run(input);
 }
 
 #[test]
@@ -51,11 +51,11 @@ fn sample() {
 }
 
 fn first_puzzle(source: &str) -> u64 {
-    total_visible_area(&parse_cubes(source))
+    total_visible_area(&profile::phase("parse", || parse_cubes(source)))
 }
 
 fn second_puzzle(source: &str) -> u64 {
-    let mut cubes = parse_cubes(source);
+    let mut cubes = profile::phase("parse", || parse_cubes(source));
 
     fill_holes(&mut cubes);
     total_visible_area(&cubes)
@@ -1,4 +1,5 @@
 use std::collections::{HashMap};
+use adventofcode_2022::{progress, runner::{Day, Solution}, trace};
 
 #[derive(Copy, Clone)]
 struct Blueprint {
@@ -147,13 +148,12 @@ impl Default for Robots {
 fn main() {
     let input = include_str!("../input/day19.input");
 
-    // Part one
-    let sum = first_puzzle(input);
-    println!("The quality level of all the blueprints is {}.", sum);
-
-    // Part two
-    let product = second_puzzle(input);
-    println!("The product of the most geodes you can get with the first three blueprints is {}.", product);
+    Day::new("day19", &[
+        "The quality level of all the blueprints is {}.",
+        "The product of the most geodes you can get with the first three blueprints is {}.",
+    ])
+    .solution(Solution::new("lotus").part(first_puzzle).part(second_puzzle))
+    .run(input);
 }
 
 #[test]
@@ -205,9 +205,17 @@ fn simulate_blueprint(
     robots: Robots,
     minutes: u64
 ) -> Resources {
+    if progress::cancelled() {
+        return resources;
+    }
+
+    progress::explore();
+
     if minutes == 0 {
         if resources.geodes > *upper_bound {
             *upper_bound = resources.geodes;
+            progress::best(*upper_bound);
+            trace!(Debug, "blueprint {}: new best of {} geodes", blueprint.index, *upper_bound);
         }
 
         return resources;
@@ -220,6 +228,7 @@ fn simulate_blueprint(
 
     let key = (resources, robots, minutes);
     if let Some(r) = cache.get(&key) {
+        trace!(Trace, "blueprint {}: cache hit with {} minutes left", blueprint.index, minutes);
         return *r;
     }
 
@@ -297,6 +306,7 @@ fn simulate_blueprint(
     }
 
     cache.insert(key, result);
+    progress::cache_size(cache.len());
 
     result
 }
\ No newline at end of file
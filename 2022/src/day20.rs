@@ -1,13 +1,14 @@
+use adventofcode_2022::runner::{Day, Solution};
+
 fn main() {
     let input = include_str!("../input/day20.input");
 
-    // Part one
-    let sum = first_puzzle(input);
-    println!("The sum of the numbers that form the grove coordinates is {}.", sum);
-
-    // Part two
-    let sum_key = second_puzzle(input);
-    println!("The sum of the numbers that form the grove coordinates (with the decryption key) is {}.", sum_key);
+    Day::new("day20", &[
+        "The sum of the numbers that form the grove coordinates is {}.",
+        "The sum of the numbers that form the grove coordinates (with the decryption key) is {}.",
+    ])
+    .solution(Solution::new("raquel").part(first_puzzle).part(second_puzzle))
+    .run(input);
 }
 
 #[test]
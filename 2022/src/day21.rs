@@ -1,4 +1,5 @@
 use std::{collections::HashMap, fmt::Debug};
+use adventofcode_2022::runner::{Day, Solution};
 
 #[derive(Debug, Clone)]
 struct Monkey {
@@ -54,11 +55,12 @@ impl Operation {
 fn main() {
     let input = include_str!("../input/day21.input");
 
-    let root = first_puzzle(input);
-    println!("The monkey named \"root\" will yell {}.", root);
-
-    let humn = second_puzzle(input);
-    println!("You must yell the number {} to pass the equality test.", humn);
+    Day::new("day21", &[
+        "The monkey named \"root\" will yell {}.",
+        "You must yell the number {} to pass the equality test.",
+    ])
+    .solution(Solution::new("raquel").part(first_puzzle).part(second_puzzle))
+    .run(input);
 }
 
 #[test]
@@ -1,4 +1,5 @@
 use std::{collections::HashMap, hash::Hash};
+use adventofcode_2022::{profile, runner::{Day, Solution}, trace};
 
 #[derive(Default)]
 struct Cube {
@@ -15,7 +16,7 @@ struct Face {
     inner_mappings: HashMap<Direction, Direction>,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 enum Direction {
     East,
     North,
@@ -98,13 +99,12 @@ enum Command {
 fn main() {
     let input = include_str!("../input/day22.input");
 
-    // Part one
-    let password_flat = first_puzzle(input);
-    println!("The password derived from the flat map is {}.", password_flat);
-
-    // Part two
-    let password_cube = second_puzzle(input);
-    println!("The password derived from the cube map is {}.", password_cube);
+    Day::new("day22", &[
+        "The password derived from the flat map is {}.",
+        "The password derived from the cube map is {}.",
+    ])
+    .solution(Solution::new("venustas").part(first_puzzle).part(second_puzzle))
+    .run(input);
 }
 
 #[test]
@@ -116,7 +116,7 @@ fn sample() {
 }
 
 fn first_puzzle(source: &str) -> i64 {
-    let (map, path, start) = parse_input(source);
+    let (map, path, start) = profile::phase("parse", || parse_input(source));
 
     let mut current = start;
     let mut delta = Vector2 { x: 1, y: 0 };
@@ -176,7 +176,7 @@ fn first_puzzle(source: &str) -> i64 {
 }
 
 fn second_puzzle(source: &str) -> i64 {
-    let (cube, path, start) = parse_cube(source);
+    let (cube, path, start) = profile::phase("parse", || parse_cube(source));
 
     let mut current = start;
 
@@ -719,6 +719,8 @@ fn parse_cube(source: &str) -> (Cube, Vec<Command>, Vector2) {
 
             this_face.mappings.insert(this_true_dir, (other_true_dir, other_face_num));
         }
+
+        trace!(Debug, "face {} with normal {:?} mapped to {:?}", this_face_num, normal, cube.faces[this_face_num].mappings);
     }
 
     (cube, path, start)
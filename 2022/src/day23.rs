@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use adventofcode_2022::{profile, runner::{Day, Solution}, trace};
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
 struct Vector2 {
@@ -17,13 +18,12 @@ enum Direction {
 fn main() {
     let input = include_str!("../input/day23.input");
 
-    // Part one
-    let empty = first_puzzle(input);
-    println!("In the smallest rectangle encompassing the Elves after 10 rounds, there are {} empty ground tiles.", empty);
-
-    // Part two
-    let round = second_puzzle(input);
-    println!("The Elves stop moving after round {}.", round);
+    Day::new("day23", &[
+        "In the smallest rectangle encompassing the Elves after 10 rounds, there are {} empty ground tiles.",
+        "The Elves stop moving after round {}.",
+    ])
+    .solution(Solution::new("morgan").part(first_puzzle).part(second_puzzle))
+    .run(input);
 }
 
 #[test]
@@ -35,7 +35,7 @@ fn sample() {
 }
 
 fn first_puzzle(source: &str) -> usize {
-    let mut map = parse_map(source);
+    let mut map = profile::phase("parse", || parse_map(source));
     let mut order = [Direction::North, Direction::South, Direction::West, Direction::East];
     let rounds = 10;
 
@@ -43,17 +43,7 @@ fn first_puzzle(source: &str) -> usize {
         simulate_round(&mut map, &mut order);
     }
 
-    let mut min = Vector2 { x: i64::MAX, y: i64::MAX };
-    let mut max = Vector2 { x: i64::MIN, y: i64::MIN };
-
-    for pos in map.keys() {
-        min.x = min.x.min(pos.x);
-        min.y = min.y.min(pos.y);
-
-        max.x = max.x.max(pos.x);
-        max.y = max.y.max(pos.y);
-    }
-
+    let (min, max) = bounds(&map);
     let mut empty = 0;
 
     for y in min.y..=max.y {
@@ -70,7 +60,7 @@ fn first_puzzle(source: &str) -> usize {
 }
 
 fn second_puzzle(source: &str) -> i64 {
-    let mut map = parse_map(source);
+    let mut map = profile::phase("parse", || parse_map(source));
     let mut order = [Direction::North, Direction::South, Direction::West, Direction::East];
     let mut num = 0;
 
@@ -78,6 +68,8 @@ fn second_puzzle(source: &str) -> i64 {
         let moved = simulate_round(&mut map, &mut order);
         num += 1;
 
+        trace!(Debug, "round {} done, elves {}", num, if moved { "moved" } else { "stayed put" });
+
         if !moved {
             return num;
         }
@@ -85,21 +77,8 @@ fn second_puzzle(source: &str) -> i64 {
 }
 
 fn simulate_round(map: &mut HashMap<Vector2, bool>, order: &mut [Direction]) -> bool {
-    /*for y in min.y..=max.y {
-        for x in min.x..=max.x {
-            let pos = Vector2 { x, y };
-
-            if map.contains_key(&pos) {
-                print!("#");
-            } else {
-                print!(".");
-            }
-        }
-        println!();
-    }
+    trace!(Trace, "elves before the round:\n{}", render(map));
 
-    println!();*/
-    
     let mut proposed = vec![];
     let mut moved = false;
 
@@ -178,6 +157,37 @@ fn peek(pos: &Vector2, order: &[Direction], map: &HashMap<Vector2, bool>) -> Opt
     None
 }
 
+fn bounds(map: &HashMap<Vector2, bool>) -> (Vector2, Vector2) {
+    let mut min = Vector2 { x: i64::MAX, y: i64::MAX };
+    let mut max = Vector2 { x: i64::MIN, y: i64::MIN };
+
+    for pos in map.keys() {
+        min.x = min.x.min(pos.x);
+        min.y = min.y.min(pos.y);
+
+        max.x = max.x.max(pos.x);
+        max.y = max.y.max(pos.y);
+    }
+
+    (min, max)
+}
+
+fn render(map: &HashMap<Vector2, bool>) -> String {
+    let (min, max) = bounds(map);
+    let mut output = String::new();
+
+    for y in min.y..=max.y {
+        for x in min.x..=max.x {
+            let pos = Vector2 { x, y };
+            output.push(if map.contains_key(&pos) { '#' } else { '.' });
+        }
+
+        output.push('\n');
+    }
+
+    output
+}
+
 fn parse_map(source: &str) -> HashMap<Vector2, bool> {
     let mut map = HashMap::<Vector2, bool>::new();
 
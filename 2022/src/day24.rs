@@ -1,4 +1,5 @@
 use std::{collections::{HashMap, HashSet, VecDeque}, hash::Hash};
+use adventofcode_2022::{profile, runner::{Day, Solution}};
 
 const STEPS: u64 = 16;
 
@@ -31,11 +32,12 @@ struct Map {
 fn main() {
     let input = include_str!("../input/day24.input");
 
-    let steps = first_puzzle(input);
-    println!("Avoiding the blizzards, the goal can be reached in {} steps.", steps);
-
-    let round = second_puzzle(input);
-    println!("A trip to the goal, back to the start and again to the goal can be done in {} steps.", round);
+    Day::new("day24", &[
+        "Avoiding the blizzards, the goal can be reached in {} steps.",
+        "A trip to the goal, back to the start and again to the goal can be done in {} steps.",
+    ])
+    .solution(Solution::new("raquel").part(first_puzzle).part(second_puzzle))
+    .run(input);
 }
 
 #[test]
@@ -47,7 +49,7 @@ fn sample() {
 }
 
 fn first_puzzle(source: &str) -> u64 {
-    let mut map = parse_map(source);
+    let mut map = profile::phase("parse", || parse_map(source));
 
     for _ in 1..=map.lcm {
         simulate_blizzards(&mut map);
@@ -60,7 +62,7 @@ fn first_puzzle(source: &str) -> u64 {
 }
 
 fn second_puzzle(source: &str) -> u64 {
-    let mut map = parse_map(source);
+    let mut map = profile::phase("parse", || parse_map(source));
 
     for _ in 1..=map.lcm {
         simulate_blizzards(&mut map);
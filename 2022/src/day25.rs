@@ -1,3 +1,5 @@
+use adventofcode_2022::runner::{Day, Solution};
+
 trait Snafu {
     type T;
     const MINUS: char = '-';
@@ -79,8 +81,11 @@ impl Snafu for i64 {
 fn main() {
     let input = include_str!("../input/day25.input");
 
-    let snafu = solve(input);
-    println!("The SNAFU number that must be supplied to Bob's console is '{}'.", snafu);
+    Day::new("day25", &[
+        "The SNAFU number that must be supplied to Bob's console is '{}'.",
+    ])
+    .solution(Solution::new("raquel").part(solve))
+    .run(input);
 }
 
 #[test]
@@ -0,0 +1,6 @@
+//! Shared harness used by the daily puzzle binaries.
+
+pub mod profile;
+pub mod progress;
+pub mod runner;
+pub mod trace;
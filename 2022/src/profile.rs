@@ -0,0 +1,199 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::RefCell,
+    sync::{atomic::{AtomicBool, AtomicUsize, Ordering}, Mutex},
+    time::{Duration, Instant},
+};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+static SEQUENCE: AtomicUsize = AtomicUsize::new(0);
+static PHASES: Mutex<Vec<(usize, Phase)>> = Mutex::new(vec![]);
+
+/// Wraps the system allocator, keeping track of live, peak and total heap usage.
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+
+        if !ptr.is_null() {
+            record_alloc(layout.size());
+        }
+
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+
+        if !new_ptr.is_null() {
+            CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+            record_alloc(new_size);
+        }
+
+        new_ptr
+    }
+}
+
+fn record_alloc(size: usize) {
+    let current = CURRENT.fetch_add(size, Ordering::Relaxed) + size;
+
+    PEAK.fetch_max(current, Ordering::Relaxed);
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    ALLOCATED.fetch_add(size, Ordering::Relaxed);
+}
+
+#[cfg(feature = "profile")]
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Whether the allocator counts are meaningful in this build.
+pub const INSTRUMENTED: bool = cfg!(feature = "profile");
+
+/// Measurements for a single phase. `peak` is the highest heap usage reached during the phase,
+/// relative to the usage when it started.
+#[derive(Clone, Debug)]
+pub struct Phase {
+    pub name: String,
+    pub elapsed: Duration,
+    pub peak: usize,
+    pub allocations: usize,
+    pub allocated: usize,
+}
+
+thread_local! {
+    static STACK: RefCell<Vec<&'static str>> = const { RefCell::new(vec![]) };
+}
+
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Runs `f` as a named phase. Nested phases are recorded as `outer/inner`.
+/// Does nothing but call `f` unless profiling was enabled.
+///
+/// The runner records every part as a phase. Anything finer, such as a `parse` phase, only shows up
+/// for the days that wrap it themselves (12, 16, 18, 22, 23 and 24 so far), elsewhere parsing
+/// is counted in the part's own row.
+pub fn phase<R>(name: &'static str, f: impl FnOnce() -> R) -> R {
+    if !enabled() {
+        return f();
+    }
+
+    let sequence = SEQUENCE.fetch_add(1, Ordering::Relaxed);
+    let path = STACK.with(|stack| {
+        let mut stack = stack.borrow_mut();
+        stack.push(name);
+        stack.join("/")
+    });
+
+    let outer_peak = PEAK.load(Ordering::Relaxed);
+    let start_current = CURRENT.load(Ordering::Relaxed);
+    let start_allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start_allocated = ALLOCATED.load(Ordering::Relaxed);
+    PEAK.store(start_current, Ordering::Relaxed);
+
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed();
+
+    let peak = PEAK.load(Ordering::Relaxed);
+    PEAK.store(peak.max(outer_peak), Ordering::Relaxed);
+
+    STACK.with(|stack| stack.borrow_mut().pop());
+    PHASES.lock().unwrap().push((sequence, Phase {
+        name: path,
+        elapsed,
+        peak: peak.saturating_sub(start_current),
+        allocations: ALLOCATIONS.load(Ordering::Relaxed) - start_allocations,
+        allocated: ALLOCATED.load(Ordering::Relaxed) - start_allocated,
+    }));
+
+    result
+}
+
+/// Returns and clears the phases recorded so far, in the order they started.
+pub fn take() -> Vec<Phase> {
+    let mut phases: Vec<(usize, Phase)> = PHASES.lock().unwrap().drain(..).collect();
+    phases.sort_by_key(|(sequence, _)| *sequence);
+
+    phases.into_iter().map(|(_, phase)| phase).collect()
+}
+
+/// Prints one row per phase, each labelled by the solution it belongs to.
+/// Heap columns are left blank unless the phases were measured with the counting allocator.
+pub fn print_table(rows: &[(String, Phase)], instrumented: bool) {
+    let label_width = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0).max("solution".len());
+    let phase_width = rows.iter().map(|(_, phase)| phase.name.len()).max().unwrap_or(0).max("phase".len());
+
+    println!(
+        "{:<label_width$}  {:<phase_width$}  {:>12}  {:>12}  {:>12}  {:>12}",
+        "solution", "phase", "time", "peak heap", "allocations", "allocated"
+    );
+
+    for (label, phase) in rows {
+        let (peak, allocations, allocated) = if instrumented {
+            (format_bytes(phase.peak), phase.allocations.to_string(), format_bytes(phase.allocated))
+        } else {
+            ("-".to_string(), "-".to_string(), "-".to_string())
+        };
+
+        println!(
+            "{:<label_width$}  {:<phase_width$}  {:>12}  {:>12}  {:>12}  {:>12}",
+            label, phase.name, format!("{:.3?}", phase.elapsed), peak, allocations, allocated
+        );
+    }
+}
+
+/// Writes a phase as a tab-separated line, so that the `aoc` binary can collect it.
+pub fn to_tsv(label: &str, phase: &Phase) -> String {
+    format!(
+        "{}\t{}\t{}\t{}\t{}\t{}",
+        label, phase.name, phase.elapsed.as_nanos(), phase.peak, phase.allocations, phase.allocated
+    )
+}
+
+pub fn from_tsv(line: &str) -> Option<(String, Phase)> {
+    let mut fields = line.split('\t');
+
+    let label = fields.next()?.to_string();
+    let name = fields.next()?.to_string();
+    let elapsed = Duration::from_nanos(fields.next()?.parse().ok()?);
+    let peak = fields.next()?.parse().ok()?;
+    let allocations = fields.next()?.parse().ok()?;
+    let allocated = fields.next()?.parse().ok()?;
+
+    Some((label, Phase { name, elapsed, peak, allocations, allocated }))
+}
+
+pub fn format_bytes(bytes: usize) -> String {
+    let units = ["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, units[0])
+    } else {
+        format!("{:.1} {}", size, units[unit])
+    }
+}
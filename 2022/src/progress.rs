@@ -0,0 +1,55 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+static CANCELLED: AtomicBool = AtomicBool::new(false);
+static EXPLORED: AtomicU64 = AtomicU64::new(0);
+static CACHE_SIZE: AtomicU64 = AtomicU64::new(0);
+static BEST: AtomicU64 = AtomicU64::new(0);
+static HAS_BEST: AtomicBool = AtomicBool::new(false);
+
+/// What a long-running solver has reported so far.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Snapshot {
+    pub explored: u64,
+    pub cache_size: u64,
+    pub best: Option<u64>,
+}
+
+/// Counts a single explored state.
+pub fn explore() {
+    EXPLORED.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn cache_size(size: usize) {
+    CACHE_SIZE.store(size as u64, Ordering::Relaxed);
+}
+
+/// Reports a candidate answer, only the highest one is kept.
+pub fn best(value: u64) {
+    BEST.fetch_max(value, Ordering::Relaxed);
+    HAS_BEST.store(true, Ordering::Relaxed);
+}
+
+/// Solvers should poll this and bail out early once it is set, their answer will be discarded.
+pub fn cancelled() -> bool {
+    CANCELLED.load(Ordering::Relaxed)
+}
+
+pub fn cancel() {
+    CANCELLED.store(true, Ordering::Relaxed);
+}
+
+pub fn reset() {
+    CANCELLED.store(false, Ordering::Relaxed);
+    EXPLORED.store(0, Ordering::Relaxed);
+    CACHE_SIZE.store(0, Ordering::Relaxed);
+    BEST.store(0, Ordering::Relaxed);
+    HAS_BEST.store(false, Ordering::Relaxed);
+}
+
+pub fn snapshot() -> Snapshot {
+    Snapshot {
+        explored: EXPLORED.load(Ordering::Relaxed),
+        cache_size: CACHE_SIZE.load(Ordering::Relaxed),
+        best: if HAS_BEST.load(Ordering::Relaxed) { Some(BEST.load(Ordering::Relaxed)) } else { None },
+    }
+}
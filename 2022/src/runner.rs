@@ -0,0 +1,344 @@
+use std::{
+    env,
+    fmt::Display,
+    process,
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{profile, progress};
+
+// Either the answer, or why there is none
+type Solver = Box<dyn Fn(&str) -> Result<String, String> + Send + Sync>;
+type Report = Box<dyn Fn(&str, &[String]) -> Result<(), String>>;
+
+const PART_NAMES: [&str; 2] = ["part 1", "part 2"];
+
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+// How long a cancelled solver gets to notice before the whole process gives up on it
+const CANCEL_GRACE: Duration = Duration::from_secs(2);
+// Solvers run on their own thread, some of them recurse quite deep
+const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+enum Mode {
+    Single,
+    All,
+    List,
+    // Whether to write tab-separated values instead of a table
+    Profile(bool),
+    Report(String, Vec<String>),
+}
+
+/// A named implementation of every part of a day.
+pub struct Solution {
+    name: &'static str,
+    parts: Vec<Solver>,
+}
+
+impl Solution {
+    pub fn new(name: &'static str) -> Self {
+        Self { name, parts: vec![] }
+    }
+
+    pub fn part<F, A>(mut self, solver: F) -> Self where F: Fn(&str) -> A + Send + Sync + 'static, A: Display {
+        self.parts.push(Box::new(move |source| Ok(solver(source).to_string())));
+        self
+    }
+
+    /// Adds a part that may find no answer for some inputs, in which case `missing` is printed instead.
+    pub fn optional_part<F, A>(mut self, solver: F, missing: &'static str) -> Self where F: Fn(&str) -> Option<A> + Send + Sync + 'static, A: Display {
+        self.parts.push(Box::new(move |source| solver(source).map(|answer| answer.to_string()).ok_or_else(|| missing.to_string())));
+        self
+    }
+}
+
+/// A day's puzzle, with the message printed for each part and every registered implementation.
+///
+/// The binary runs the first implementation by default. `--impl <name>` picks another one,
+/// `--all` runs all of them and compares their answers and timings, and `--list` prints their names.
+/// `--profile` reports the time and heap usage of every phase, see [`profile`]. Every part is
+/// a phase, parsing only gets its own row in days that wrap it in [`profile::phase`].
+/// `--timeout <seconds>` gives up on any part that runs for longer, see [`progress`].
+/// `--report <name> [args...]` runs one of the day's extra reports on the input instead.
+pub struct Day {
+    name: &'static str,
+    messages: Vec<&'static str>,
+    solutions: Vec<Solution>,
+    reports: Vec<(&'static str, Report)>,
+    timeout: Option<Duration>,
+}
+
+impl Day {
+    pub fn new(name: &'static str, messages: &[&'static str]) -> Self {
+        Self {
+            name,
+            messages: messages.to_vec(),
+            solutions: vec![],
+            reports: vec![],
+            timeout: None,
+        }
+    }
+
+    pub fn solution(mut self, solution: Solution) -> Self {
+        assert_eq!(solution.parts.len(), self.messages.len(), "Solution '{}' must solve every part.", solution.name);
+        assert!(self.find(solution.name).is_none(), "Solution '{}' was registered twice.", solution.name);
+
+        self.solutions.push(solution);
+        self
+    }
+
+    /// Registers a report, which gets the input and any arguments following its name.
+    /// Reports returning an error are treated like any other invalid argument.
+    pub fn report<F>(mut self, name: &'static str, report: F) -> Self where F: Fn(&str, &[String]) -> Result<(), String> + 'static {
+        assert!(self.reports.iter().all(|(existing, _)| *existing != name), "Report '{}' was registered twice.", name);
+
+        self.reports.push((name, Box::new(report)));
+        self
+    }
+
+    pub fn run(mut self, input: &str) {
+        let mut args = env::args().skip(1);
+        let mut selected = None;
+        let mut mode = Mode::Single;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--impl" => {
+                    let name = args.next().unwrap_or_else(|| self.exit("Expected an implementation name after '--impl'."));
+                    if self.find(&name).is_none() {
+                        self.exit(&format!("Unknown implementation '{}'.", name));
+                    }
+
+                    selected = Some(name);
+                }
+                "--timeout" => {
+                    let seconds = args
+                        .next()
+                        .and_then(|seconds| seconds.parse::<f64>().ok())
+                        .filter(|seconds| *seconds > 0.0)
+                        .unwrap_or_else(|| self.exit("Expected a number of seconds after '--timeout'."));
+
+                    self.timeout = Some(Duration::from_secs_f64(seconds));
+                }
+                "--all" => mode = Mode::All,
+                "--list" => mode = Mode::List,
+                "--profile" => mode = Mode::Profile(false),
+                "--profile=tsv" => mode = Mode::Profile(true),
+                "--report" => {
+                    let name = args.next().unwrap_or_else(|| self.exit("Expected a report name after '--report'."));
+                    if self.reports.iter().all(|(report, _)| *report != name) {
+                        self.exit(&format!("Unknown report '{}'.", name));
+                    }
+
+                    // Everything after the name belongs to the report
+                    mode = Mode::Report(name, args.by_ref().collect());
+                }
+                _ => self.exit(&format!("Unknown argument '{}'.", arg)),
+            }
+        }
+
+        let selected = selected.map(|name| self.find(&name).unwrap());
+        let solution = selected.unwrap_or(&self.solutions[0]);
+
+        match mode {
+            Mode::Single => self.run_single(solution, input),
+            Mode::All => self.run_all(input),
+            Mode::List => {
+                for solution in &self.solutions {
+                    println!("{}::{}", self.name, solution.name);
+                }
+
+                for (name, _) in &self.reports {
+                    println!("--report {}", name);
+                }
+            }
+            Mode::Report(name, args) => {
+                let (_, report) = self.reports.iter().find(|(report, _)| *report == name).unwrap();
+                if let Err(message) = report(input, &args) {
+                    self.exit(&message);
+                }
+            }
+            Mode::Profile(tsv) => {
+                // Without an explicit choice, every implementation gets profiled
+                let solutions = match selected {
+                    Some(solution) => vec![solution],
+                    None => self.solutions.iter().collect(),
+                };
+
+                self.run_profile(&solutions, input, tsv);
+            }
+        }
+    }
+
+    fn find(&self, name: &str) -> Option<&Solution> {
+        self.solutions.iter().find(|solution| solution.name == name)
+    }
+
+    fn run_single(&self, solution: &Solution, input: &str) {
+        for (index, message) in self.messages.iter().enumerate() {
+            match self.solve(solution, index, input) {
+                Some(Ok(answer)) => println!("{}", message.replacen("{}", &answer, 1)),
+                Some(Err(missing)) => println!("Part {}: {}", index + 1, missing),
+                None => println!("Part {} timed out.", index + 1),
+            }
+        }
+    }
+
+    // Runs a part on its own thread, reporting its progress and cancelling it once it runs out of time
+    fn solve(&self, solution: &Solution, index: usize, input: &str) -> Option<Result<String, String>> {
+        let label = format!("{}::{} {}", self.name, solution.name, PART_NAMES[index]);
+        let part = &solution.parts[index];
+
+        progress::reset();
+        let start = Instant::now();
+        let (sender, receiver) = mpsc::channel();
+
+        thread::scope(|scope| {
+            thread::Builder::new()
+                .stack_size(STACK_SIZE)
+                .spawn_scoped(scope, move || {
+                    let answer = profile::phase(PART_NAMES[index], || part(input));
+                    let _ = sender.send(answer);
+                })
+                .expect("Could not spawn the solver thread.");
+
+            loop {
+                let wait = match self.timeout {
+                    Some(timeout) => PROGRESS_INTERVAL.min(timeout.saturating_sub(start.elapsed())),
+                    None => PROGRESS_INTERVAL,
+                };
+
+                match receiver.recv_timeout(wait) {
+                    Ok(answer) => return Some(answer),
+                    // The solver panicked, which the scope passes on once it ends
+                    Err(RecvTimeoutError::Disconnected) => return None,
+                    Err(RecvTimeoutError::Timeout) => {}
+                }
+
+                if self.timeout.is_some_and(|timeout| start.elapsed() >= timeout) {
+                    progress::cancel();
+                    eprintln!("{}: timed out after {:.1?}.", label, start.elapsed());
+
+                    if receiver.recv_timeout(CANCEL_GRACE).is_err() {
+                        eprintln!("{}: did not stop when cancelled, giving up.", label);
+                        process::exit(124);
+                    }
+
+                    return None;
+                }
+
+                report_progress(&label, start.elapsed());
+            }
+        })
+    }
+
+    fn run_all(&self, input: &str) {
+        let results: Vec<Vec<(String, Duration)>> = self.solutions
+            .iter()
+            .map(|solution| {
+                (0..self.messages.len())
+                    .map(|index| {
+                        let start = Instant::now();
+                        let answer = match self.solve(solution, index, input) {
+                            Some(Ok(answer)) | Some(Err(answer)) => answer,
+                            None => "timed out".to_string(),
+                        };
+
+                        (answer, start.elapsed())
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let name_width = self.solutions
+            .iter()
+            .map(|solution| self.name.len() + 2 + solution.name.len())
+            .max()
+            .unwrap_or(0);
+
+        for part in 0..self.messages.len() {
+            println!("Part {}:", part + 1);
+
+            let reference = &results[0][part].0;
+            for (solution, result) in self.solutions.iter().zip(&results) {
+                let (answer, elapsed) = &result[part];
+                let marker = if answer == reference { "" } else { "  <- differs" };
+
+                println!(
+                    "  {:<width$}  {:>12.3?}  {}{}",
+                    format!("{}::{}", self.name, solution.name),
+                    elapsed,
+                    answer,
+                    marker,
+                    width = name_width
+                );
+            }
+        }
+
+        let disagreements = (0..self.messages.len())
+            .filter(|&part| results.iter().any(|result| result[part].0 != results[0][part].0))
+            .count();
+
+        if disagreements > 0 {
+            println!("Implementations disagree on {} part(s).", disagreements);
+            process::exit(1);
+        }
+    }
+
+    fn run_profile(&self, solutions: &[&Solution], input: &str, tsv: bool) {
+        profile::enable();
+
+        let mut rows = vec![];
+
+        for solution in solutions {
+            let label = format!("{}::{}", self.name, solution.name);
+
+            for index in 0..solution.parts.len() {
+                self.solve(solution, index, input);
+            }
+
+            rows.extend(profile::take().into_iter().map(|phase| (label.clone(), phase)));
+        }
+
+        if tsv {
+            for (label, phase) in &rows {
+                println!("{}", profile::to_tsv(label, phase));
+            }
+        } else {
+            if !profile::INSTRUMENTED {
+                println!("Built without the 'profile' feature, only timings are available.");
+            }
+
+            profile::print_table(&rows, profile::INSTRUMENTED);
+        }
+    }
+
+    fn exit(&self, message: &str) -> ! {
+        eprintln!("{}: {}", self.name, message);
+        eprintln!(
+            "Usage: {} [--impl <name>] [--timeout <seconds>] [--all | --list | --profile[=tsv] | --report <name> [args...]]",
+            self.name
+        );
+        process::exit(2);
+    }
+}
+
+fn report_progress(label: &str, elapsed: Duration) {
+    let snapshot = progress::snapshot();
+    let mut line = format!("{}: running for {:.0?}", label, elapsed);
+
+    if snapshot.explored > 0 {
+        line += &format!(", {} states explored", snapshot.explored);
+    }
+
+    if snapshot.cache_size > 0 {
+        line += &format!(", {} cached", snapshot.cache_size);
+    }
+
+    if let Some(best) = snapshot.best {
+        line += &format!(", best so far {}", best);
+    }
+
+    eprintln!("{}", line);
+}
@@ -0,0 +1,125 @@
+//! Trace events for debugging solvers, filtered through the `AOC_TRACE` environment variable.
+//!
+//! `AOC_TRACE` is a comma separated list of `day=level` filters, a lone level applies to every day:
+//! `AOC_TRACE=info,day17=debug`. Events are only compiled in with the `trace` feature, otherwise
+//! [`trace!`](crate::trace!) expands to code that never runs.
+
+use std::{env, fmt, sync::OnceLock};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    fn parse(source: &str) -> Option<Level> {
+        match source.to_ascii_lowercase().as_str() {
+            "error" => Some(Level::Error),
+            "warn" => Some(Level::Warn),
+            "info" => Some(Level::Info),
+            "debug" => Some(Level::Debug),
+            "trace" => Some(Level::Trace),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        };
+
+        write!(fmt, "{}", name)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Filters {
+    default: Option<Level>,
+    days: Vec<(String, Level)>,
+}
+
+impl Filters {
+    fn parse(source: &str) -> Filters {
+        let mut filters = Filters::default();
+
+        for directive in source.split(',').map(str::trim).filter(|directive| !directive.is_empty()) {
+            match directive.split_once('=') {
+                Some((day, level)) => match Level::parse(level) {
+                    Some(level) => filters.days.push((day.to_string(), level)),
+                    None => eprintln!("AOC_TRACE: ignoring unknown level '{}'.", level),
+                },
+                None => match Level::parse(directive) {
+                    Some(level) => filters.default = Some(level),
+                    None => eprintln!("AOC_TRACE: ignoring unknown level '{}'.", directive),
+                },
+            }
+        }
+
+        filters
+    }
+
+    fn max_level(&self, target: &str) -> Option<Level> {
+        self.days
+            .iter()
+            .rev()
+            .find(|(day, _)| day == target)
+            .map(|(_, level)| *level)
+            .or(self.default)
+    }
+}
+
+static FILTERS: OnceLock<Filters> = OnceLock::new();
+
+/// Whether events of `level` from `target` (the day's binary name) should be printed.
+pub fn enabled(target: &str, level: Level) -> bool {
+    let filters = FILTERS.get_or_init(|| Filters::parse(&env::var("AOC_TRACE").unwrap_or_default()));
+
+    filters.max_level(target).is_some_and(|max| level <= max)
+}
+
+pub fn emit(target: &str, level: Level, args: fmt::Arguments) {
+    eprintln!("[{} {}] {}", target, level, args);
+}
+
+/// Emits a trace event from the calling day, such as `trace!(Debug, "cycle found after {} rocks", index)`.
+#[cfg(feature = "trace")]
+#[macro_export]
+macro_rules! trace {
+    ($level:ident, $($arg:tt)+) => {
+        if $crate::trace::enabled(env!("CARGO_BIN_NAME"), $crate::trace::Level::$level) {
+            $crate::trace::emit(env!("CARGO_BIN_NAME"), $crate::trace::Level::$level, format_args!($($arg)+));
+        }
+    };
+}
+
+// Still type-checks the arguments, so that enabling the feature doesn't break the build
+#[cfg(not(feature = "trace"))]
+#[macro_export]
+macro_rules! trace {
+    ($level:ident, $($arg:tt)+) => {
+        if false {
+            let _ = $crate::trace::Level::$level;
+            let _ = format_args!($($arg)+);
+        }
+    };
+}
+
+#[test]
+fn filters() {
+    let filters = Filters::parse("info, day17=debug,day13=trace,day05=nonsense");
+
+    assert_eq!(filters.max_level("day01"), Some(Level::Info));
+    assert_eq!(filters.max_level("day17"), Some(Level::Debug));
+    assert_eq!(filters.max_level("day13"), Some(Level::Trace));
+    assert_eq!(Filters::parse("day17=debug").max_level("day01"), None);
+}